@@ -0,0 +1,775 @@
+use crate::input::Input;
+
+use failure::{bail, format_err};
+use glfw::{self, Key};
+use serde::Deserialize;
+use std::{collections::BTreeMap, fmt, fs, io::Read, path::Path};
+
+/// Something the user can do from the keyboard
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Action {
+    // scene
+    Quit,
+    ToggleWireframe,
+    ToggleCameraMode,
+    /// Follows the selected model with the active camera
+    ToggleFollow,
+    CycleLayout,
+    ToggleProjection,
+    TogglePath,
+    FocusOrigin,
+    FocusSelection,
+    /// Focuses on the surface under the cursor
+    FocusCursor,
+    SelectNext,
+    SelectPrevious,
+    /// Types the name of the models to select in the window
+    SelectByName,
+    /// Selects the n-th model alone
+    Select(usize),
+    /// Adds or removes the n-th model from the selection
+    ToggleSelect(usize),
+    RecallBookmark(usize),
+    StoreBookmark(usize),
+    TogglePause,
+    Step,
+    Slower,
+    Faster,
+    ShowCulling,
+    Screenshot,
+    ToggleRecording,
+    ToggleFullscreen,
+    /// Copies the selected models
+    Duplicate,
+    Delete,
+    /// Adds a model at the camera focus
+    Spawn,
+    /// Changes the model added by `Spawn`
+    CycleSpawnModel,
+
+    // camera
+    CameraOrbitUp,
+    CameraOrbitDown,
+    CameraOrbitLeft,
+    CameraOrbitRight,
+    CameraSlideForward,
+    CameraSlideBackward,
+    CameraSlideLeft,
+    CameraSlideRight,
+    FlyForward,
+    FlyBackward,
+    FlyLeft,
+    FlyRight,
+    FlyUp,
+    FlyDown,
+    ZoomIn,
+    ZoomOut,
+    LookAtOrigin,
+    ViewFront,
+    ViewSide,
+    ViewTop,
+    DebugCamera,
+
+    // selected models
+    SlideForwardX,
+    SlideBackwardX,
+    SlideForwardY,
+    SlideBackwardY,
+    SlideForwardZ,
+    SlideBackwardZ,
+    CurveForwardX,
+    CurveBackwardX,
+    CurveForwardY,
+    CurveBackwardY,
+    CurveForwardZ,
+    CurveBackwardZ,
+    ScaleUp,
+    ScaleDown,
+    FaceOrigin,
+    FaceAboveOrigin,
+    OrbitForward,
+    OrbitBackward,
+    RotateForwardX,
+    RotateBackwardX,
+    RotateForwardY,
+    RotateBackwardY,
+    RotateForwardZ,
+    RotateBackwardZ,
+    StartAnimation,
+    CycleSpace,
+    CyclePivot,
+    PinPivot,
+    DebugModel,
+}
+
+/// Actions with a number go from 1 to `SLOTS`, like the number keys
+pub const SLOTS: usize = 9;
+
+/// Modifier keys that must be held. Others may be held too, unless a
+/// binding of the same key uses them.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Mods {
+    pub control: bool,
+    pub alt: bool,
+    pub shift: bool,
+}
+
+impl Mods {
+    /// Whether every modifier of `other` is in these
+    fn contains(self, other: Mods) -> bool {
+        (self.control || !other.control) && (self.alt || !other.alt) && (self.shift || !other.shift)
+    }
+}
+
+/// Key and modifiers, written like `Ctrl+Shift+F1`. A chord like `G+X`
+/// needs `G` held down when `X` is pressed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Binding {
+    pub mods: Mods,
+    pub chord: Option<Key>,
+    pub key: Key,
+}
+
+/// Keys given to each action in a bindings file, the missing actions keep
+/// their default keys
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+pub struct BindingsFile(pub BTreeMap<String, Keys>);
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(untagged)]
+pub enum Keys {
+    One(String),
+    Many(Vec<String>),
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Bindings {
+    bindings: Vec<(Binding, Action)>,
+}
+
+impl Action {
+    pub fn all() -> Vec<Action> {
+        use self::Action::*;
+        let mut all = vec![
+            Quit,
+            ToggleWireframe,
+            ToggleCameraMode,
+            ToggleFollow,
+            CycleLayout,
+            ToggleProjection,
+            TogglePath,
+            FocusOrigin,
+            FocusSelection,
+            FocusCursor,
+            SelectNext,
+            SelectPrevious,
+            SelectByName,
+            TogglePause,
+            Step,
+            Slower,
+            Faster,
+            ShowCulling,
+            Screenshot,
+            ToggleRecording,
+            ToggleFullscreen,
+            Duplicate,
+            Delete,
+            Spawn,
+            CycleSpawnModel,
+            CameraOrbitUp,
+            CameraOrbitDown,
+            CameraOrbitLeft,
+            CameraOrbitRight,
+            CameraSlideForward,
+            CameraSlideBackward,
+            CameraSlideLeft,
+            CameraSlideRight,
+            FlyForward,
+            FlyBackward,
+            FlyLeft,
+            FlyRight,
+            FlyUp,
+            FlyDown,
+            ZoomIn,
+            ZoomOut,
+            LookAtOrigin,
+            ViewFront,
+            ViewSide,
+            ViewTop,
+            DebugCamera,
+            SlideForwardX,
+            SlideBackwardX,
+            SlideForwardY,
+            SlideBackwardY,
+            SlideForwardZ,
+            SlideBackwardZ,
+            CurveForwardX,
+            CurveBackwardX,
+            CurveForwardY,
+            CurveBackwardY,
+            CurveForwardZ,
+            CurveBackwardZ,
+            ScaleUp,
+            ScaleDown,
+            FaceOrigin,
+            FaceAboveOrigin,
+            OrbitForward,
+            OrbitBackward,
+            RotateForwardX,
+            RotateBackwardX,
+            RotateForwardY,
+            RotateBackwardY,
+            RotateForwardZ,
+            RotateBackwardZ,
+            StartAnimation,
+            CycleSpace,
+            CyclePivot,
+            PinPivot,
+            DebugModel,
+        ];
+        for n in 1..=SLOTS {
+            all.extend(&[
+                Select(n),
+                ToggleSelect(n),
+                RecallBookmark(n),
+                StoreBookmark(n),
+            ]);
+        }
+        all
+    }
+
+    /// Name used in bindings files, like `slide_forward_z` or `select_3`
+    pub fn name(self) -> String {
+        let (debug, n) = match self {
+            Action::Select(n) => ("Select".into(), Some(n)),
+            Action::ToggleSelect(n) => ("ToggleSelect".into(), Some(n)),
+            Action::RecallBookmark(n) => ("RecallBookmark".into(), Some(n)),
+            Action::StoreBookmark(n) => ("StoreBookmark".into(), Some(n)),
+            action => (format!("{:?}", action), None),
+        };
+        let mut name = String::new();
+        for c in debug.chars() {
+            if c.is_uppercase() && !name.is_empty() {
+                name.push('_');
+            }
+            name.extend(c.to_lowercase());
+        }
+        if let Some(n) = n {
+            name += &format!("_{}", n);
+        }
+        name
+    }
+
+    pub fn from_name(name: &str) -> Option<Action> {
+        Action::all().into_iter().find(|a| a.name() == name)
+    }
+}
+
+impl Binding {
+    pub fn new(key: Key, mods: Mods) -> Binding {
+        Binding {
+            mods,
+            chord: None,
+            key,
+        }
+    }
+
+    /// Parses `Key`, `Shift+Key`, `Ctrl+Alt+Key`, `Chord+Key` and so on,
+    /// keys are named like `glfw::Key`
+    pub fn parse(s: &str) -> Result<Binding, failure::Error> {
+        let mut parts: Vec<_> = s.split('+').map(str::trim).collect();
+        let key = parts.pop().unwrap_or_default();
+        let key = parse_key(key).ok_or_else(|| format_err!("Unknown key {:?} in {:?}", key, s))?;
+
+        let mut binding = Binding::new(key, Mods::default());
+        for m in parts {
+            match m.to_lowercase().as_str() {
+                "ctrl" | "control" => binding.mods.control = true,
+                "alt" => binding.mods.alt = true,
+                "shift" => binding.mods.shift = true,
+                _ => match parse_key(m) {
+                    Some(chord) if binding.chord.is_none() && chord != key => {
+                        binding.chord = Some(chord)
+                    }
+                    Some(_) => bail!("Chords have two keys, {:?} has more", s),
+                    None => bail!("Unknown modifier or key {:?} in {:?}", m, s),
+                },
+            }
+        }
+        Ok(binding)
+    }
+
+    /// Whether the keys are held down with at least these modifiers
+    fn is_down(&self, input: &Input) -> bool {
+        input.mods().contains(self.mods)
+            && input.is_pressed(self.key)
+            && self.chord.into_iter().all(|c| input.is_pressed(c))
+    }
+}
+
+fn parse_key(name: &str) -> Option<Key> {
+    KEYS.iter()
+        .cloned()
+        .find(|k| format!("{:?}", k).eq_ignore_ascii_case(name))
+}
+
+impl fmt::Display for Binding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.mods.control {
+            write!(f, "Ctrl+")?;
+        }
+        if self.mods.alt {
+            write!(f, "Alt+")?;
+        }
+        if self.mods.shift {
+            write!(f, "Shift+")?;
+        }
+        if let Some(chord) = self.chord {
+            write!(f, "{:?}+", chord)?;
+        }
+        write!(f, "{:?}", self.key)
+    }
+}
+
+impl Default for Bindings {
+    fn default() -> Self {
+        Bindings::new(BindingsFile::default()).expect("Default key bindings conflict")
+    }
+}
+
+impl Bindings {
+    /// Default bindings with the actions of `file` replaced, failing when a
+    /// key ends up used by two actions
+    pub fn new(file: BindingsFile) -> Result<Bindings, failure::Error> {
+        let mut keys: BTreeMap<Action, Vec<String>> = BTreeMap::new();
+        for (action, key) in defaults() {
+            keys.entry(action).or_default().push(key);
+        }
+        for (name, k) in file.0 {
+            let action = Action::from_name(&name)
+                .ok_or_else(|| format_err!("Unknown action {:?} in key bindings", name))?;
+            let k = match k {
+                Keys::One(k) => vec![k],
+                Keys::Many(k) => k,
+            };
+            keys.insert(action, k);
+        }
+
+        let mut bindings: Vec<(Binding, Action)> = Vec::new();
+        for (action, keys) in keys {
+            for key in keys {
+                let binding = Binding::parse(&key)?;
+                if let Some((_, other)) = bindings.iter().find(|(b, _)| *b == binding) {
+                    bail!(
+                        "Key {} is bound to both {} and {}",
+                        binding,
+                        other.name(),
+                        action.name()
+                    );
+                }
+                bindings.push((binding, action));
+            }
+        }
+        Ok(Bindings { bindings })
+    }
+
+    pub fn from_path<P>(path: P) -> Result<Bindings, failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut content = String::default();
+        fs::File::open(path)?.read_to_string(&mut content)?;
+        let file = serde_json::from_str(&content)
+            .map_err(|e| format_err!("Failed to read key bindings file: {:#?}", e))?;
+        Bindings::new(file)
+    }
+
+    /// Bindings of `action` whose keys are down. A chord hides the
+    /// bindings of its keys alone, so `G+X` does neither `G` nor `X`, and a
+    /// binding with more of the held modifiers hides the ones with fewer, so
+    /// `Ctrl+W` doesn't also do `W`.
+    fn down<'a>(&'a self, input: &'a Input, action: Action) -> impl Iterator<Item = Binding> + 'a {
+        let in_chord = move |b: Binding| {
+            self.bindings.iter().any(|&(other, _)| {
+                other.chord.is_some()
+                    && (other.chord == Some(b.key) || other.key == b.key)
+                    && other.is_down(input)
+            })
+        };
+        let has_more_mods = move |b: Binding| {
+            self.bindings.iter().any(|&(other, _)| {
+                other.key == b.key
+                    && other.chord == b.chord
+                    && other.mods != b.mods
+                    && other.mods.contains(b.mods)
+                    && other.is_down(input)
+            })
+        };
+        self.bindings
+            .iter()
+            .filter(move |&&(b, a)| a == action && b.is_down(input))
+            .filter(move |&&(b, _)| b.chord.is_some() || !in_chord(b))
+            .filter(move |&&(b, _)| !has_more_mods(b))
+            .map(|&(b, _)| b)
+    }
+
+    /// Whether a key of `action` is held down with its modifiers, or a
+    /// gamepad button of it
+    pub fn is_held(&self, input: &Input, action: Action) -> bool {
+        self.down(input, action).next().is_some() || input.gamepad.actions.contains(&action)
+    }
+
+    /// Whether `action` started in this frame
+    pub fn just_pressed(&self, input: &Input, action: Action) -> bool {
+        self.down(input, action).any(|b| input.just_pressed(b.key))
+            || input.gamepad_just_pressed(action)
+    }
+
+    /// Whether the key of `action` went up in this frame, the modifiers and
+    /// chord key still being held
+    pub fn just_released(&self, input: &Input, action: Action) -> bool {
+        self.bindings.iter().any(|&(b, a)| {
+            a == action
+                && input.mods().contains(b.mods)
+                && input.just_released(b.key)
+                && b.chord.into_iter().all(|c| input.is_pressed(c))
+        }) || input.gamepad_just_released(action)
+    }
+
+    /// Seconds `action` has been held down, `None` when it isn't
+    pub fn held_for(&self, input: &Input, action: Action) -> Option<f32> {
+        self.down(input, action)
+            .filter_map(|b| input.held_for(b.key))
+            .fold(None, |longest: Option<f32>, t| {
+                Some(longest.map_or(t, |l| l.max(t)))
+            })
+    }
+
+    /// Actions started in this frame
+    pub fn just_pressed_actions(&self, input: &Input) -> Vec<Action> {
+        let mut actions: Vec<_> = self
+            .bindings
+            .iter()
+            .map(|&(_, a)| a)
+            .chain(input.gamepad.actions.iter().cloned())
+            .filter(|&a| self.just_pressed(input, a))
+            .collect();
+        actions.sort();
+        actions.dedup();
+        actions
+    }
+}
+
+/// The keys used before bindings could be configured
+fn defaults() -> Vec<(Action, String)> {
+    use self::Action::*;
+    let mut defaults: Vec<(Action, String)> = [
+        (Quit, "Escape"),
+        (ToggleWireframe, "T"),
+        (ToggleCameraMode, "M"),
+        (ToggleFollow, "Shift+M"),
+        (CycleLayout, "L"),
+        (ToggleProjection, "Kp0"),
+        (TogglePath, "Y"),
+        (FocusOrigin, "Home"),
+        (FocusSelection, "Period"),
+        (FocusCursor, "Shift+Period"),
+        (SelectNext, "Tab"),
+        (SelectPrevious, "Shift+Tab"),
+        (SelectByName, "Slash"),
+        (TogglePause, "Space"),
+        (Step, "RightBracket"),
+        (Slower, "Minus"),
+        (Faster, "Equal"),
+        (ShowCulling, "B"),
+        (Screenshot, "F12"),
+        (ToggleRecording, "F10"),
+        (ToggleFullscreen, "F11"),
+        (Duplicate, "Ctrl+D"),
+        (Delete, "Delete"),
+        (Spawn, "Insert"),
+        (CycleSpawnModel, "Shift+Insert"),
+        (CameraOrbitUp, "Up"),
+        (CameraOrbitDown, "Down"),
+        (CameraOrbitLeft, "Left"),
+        (CameraOrbitRight, "Right"),
+        (CameraSlideForward, "Shift+Up"),
+        (CameraSlideBackward, "Shift+Down"),
+        (CameraSlideLeft, "Shift+Left"),
+        (CameraSlideRight, "Shift+Right"),
+        (FlyForward, "Kp8"),
+        (FlyBackward, "Kp5"),
+        (FlyLeft, "Kp4"),
+        (FlyRight, "Kp6"),
+        (FlyUp, "Kp9"),
+        (FlyDown, "Kp7"),
+        (ZoomIn, "I"),
+        (ZoomOut, "O"),
+        (LookAtOrigin, "K"),
+        (ViewFront, "Kp1"),
+        (ViewSide, "Kp3"),
+        (ViewTop, "Kp2"),
+        (DebugCamera, "J"),
+        (SlideForwardZ, "W"),
+        (SlideBackwardX, "A"),
+        (SlideBackwardZ, "S"),
+        (SlideForwardX, "D"),
+        (SlideForwardY, "Q"),
+        (SlideBackwardY, "E"),
+        (CurveForwardZ, "Shift+W"),
+        (CurveBackwardX, "Shift+A"),
+        (CurveBackwardZ, "Shift+S"),
+        (CurveForwardX, "Shift+D"),
+        (CurveForwardY, "Shift+Q"),
+        (CurveBackwardY, "Shift+E"),
+        (ScaleUp, "R"),
+        (ScaleDown, "Shift+R"),
+        (FaceOrigin, "G"),
+        (FaceAboveOrigin, "Shift+G"),
+        (OrbitForward, "V"),
+        (OrbitBackward, "Shift+V"),
+        (RotateForwardZ, "Z"),
+        (RotateBackwardZ, "Shift+Z"),
+        (RotateForwardX, "X"),
+        (RotateBackwardX, "Shift+X"),
+        (RotateForwardY, "C"),
+        (RotateBackwardY, "Shift+C"),
+        (StartAnimation, "H"),
+        (CycleSpace, "N"),
+        (CyclePivot, "P"),
+        (PinPivot, "Shift+P"),
+        (DebugModel, "F"),
+    ]
+    .iter()
+    .map(|&(a, k)| (a, k.to_string()))
+    .collect();
+
+    for n in 1..=SLOTS {
+        defaults.push((Select(n), format!("Num{}", n)));
+        defaults.push((ToggleSelect(n), format!("Ctrl+Num{}", n)));
+        defaults.push((RecallBookmark(n), format!("F{}", n)));
+        defaults.push((StoreBookmark(n), format!("Ctrl+F{}", n)));
+    }
+    defaults
+}
+
+/// Keys that can be bound, the modifier keys are left out
+pub const KEYS: &[Key] = &[
+    Key::Space,
+    Key::Apostrophe,
+    Key::Comma,
+    Key::Minus,
+    Key::Period,
+    Key::Slash,
+    Key::Num0,
+    Key::Num1,
+    Key::Num2,
+    Key::Num3,
+    Key::Num4,
+    Key::Num5,
+    Key::Num6,
+    Key::Num7,
+    Key::Num8,
+    Key::Num9,
+    Key::Semicolon,
+    Key::Equal,
+    Key::A,
+    Key::B,
+    Key::C,
+    Key::D,
+    Key::E,
+    Key::F,
+    Key::G,
+    Key::H,
+    Key::I,
+    Key::J,
+    Key::K,
+    Key::L,
+    Key::M,
+    Key::N,
+    Key::O,
+    Key::P,
+    Key::Q,
+    Key::R,
+    Key::S,
+    Key::T,
+    Key::U,
+    Key::V,
+    Key::W,
+    Key::X,
+    Key::Y,
+    Key::Z,
+    Key::LeftBracket,
+    Key::Backslash,
+    Key::RightBracket,
+    Key::GraveAccent,
+    Key::Escape,
+    Key::Enter,
+    Key::Tab,
+    Key::Backspace,
+    Key::Insert,
+    Key::Delete,
+    Key::Right,
+    Key::Left,
+    Key::Down,
+    Key::Up,
+    Key::PageUp,
+    Key::PageDown,
+    Key::Home,
+    Key::End,
+    Key::PrintScreen,
+    Key::Pause,
+    Key::F1,
+    Key::F2,
+    Key::F3,
+    Key::F4,
+    Key::F5,
+    Key::F6,
+    Key::F7,
+    Key::F8,
+    Key::F9,
+    Key::F10,
+    Key::F11,
+    Key::F12,
+    Key::Kp0,
+    Key::Kp1,
+    Key::Kp2,
+    Key::Kp3,
+    Key::Kp4,
+    Key::Kp5,
+    Key::Kp6,
+    Key::Kp7,
+    Key::Kp8,
+    Key::Kp9,
+    Key::KpDecimal,
+    Key::KpDivide,
+    Key::KpMultiply,
+    Key::KpSubtract,
+    Key::KpAdd,
+    Key::KpEnter,
+    Key::KpEqual,
+    Key::Menu,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn action_names() {
+        assert_eq!("slide_forward_z", Action::SlideForwardZ.name());
+        assert_eq!("store_bookmark_3", Action::StoreBookmark(3).name());
+        assert_eq!(Some(Action::Select(9)), Action::from_name("select_9"));
+        assert_eq!(None, Action::from_name("select_10"));
+    }
+
+    #[test]
+    fn parse_binding() {
+        let b = Binding::parse("Ctrl+Shift+f1").unwrap();
+        assert_eq!(
+            Binding::new(
+                Key::F1,
+                Mods {
+                    control: true,
+                    alt: false,
+                    shift: true
+                }
+            ),
+            b
+        );
+        assert_eq!("Ctrl+Shift+F1", b.to_string());
+        assert!(Binding::parse("Hyper+A").is_err());
+    }
+
+    #[test]
+    fn overrides_and_conflicts() {
+        let defaults = Bindings::default();
+        let shift_w = Input::with_keys(&[Key::LeftShift, Key::W]);
+        assert!(defaults.is_held(&shift_w, Action::CurveForwardZ));
+        assert!(!defaults.is_held(&shift_w, Action::SlideForwardZ));
+
+        let file: BindingsFile = serde_json::from_value(json!({
+            "slide_forward_z": ["Up", "K"],
+        }))
+        .unwrap();
+        let err = Bindings::new(file).unwrap_err().to_string();
+        assert!(err.contains("camera_orbit_up"));
+
+        let file: BindingsFile = serde_json::from_value(json!({
+            "slide_forward_z": "U",
+            "toggle_wireframe": "W",
+        }))
+        .unwrap();
+        let bindings = Bindings::new(file).unwrap();
+        assert!(bindings.is_held(&Input::with_keys(&[Key::W]), Action::ToggleWireframe));
+        assert!(bindings.is_held(&Input::with_keys(&[Key::U]), Action::SlideForwardZ));
+
+        // Extra modifiers are ignored unless the key is bound with them
+        let file: BindingsFile = serde_json::from_value(json!({
+            "toggle_wireframe": "Ctrl+W",
+        }))
+        .unwrap();
+        let bindings = Bindings::new(file).unwrap();
+        let ctrl_w = Input::with_keys(&[Key::LeftControl, Key::W]);
+        assert!(bindings.is_held(&ctrl_w, Action::ToggleWireframe));
+        assert!(!bindings.is_held(&ctrl_w, Action::SlideForwardZ));
+        let ctrl_a = Input::with_keys(&[Key::LeftControl, Key::A]);
+        assert!(bindings.is_held(&ctrl_a, Action::SlideBackwardX));
+    }
+
+    #[test]
+    fn edges_and_chords() {
+        let file: BindingsFile = serde_json::from_value(json!({
+            "rotate_forward_x": "G+X",
+        }))
+        .unwrap();
+        let bindings = Bindings::new(file).unwrap();
+        let mut input = Input::default();
+
+        input.update_keys(&[Key::G], 0.1);
+        assert!(bindings.just_pressed(&input, Action::FaceOrigin));
+        input.update_keys(&[Key::G], 0.1);
+        assert!(!bindings.just_pressed(&input, Action::FaceOrigin));
+        assert_eq!(Some(0.1), bindings.held_for(&input, Action::FaceOrigin));
+
+        input.update_keys(&[Key::G, Key::X], 0.1);
+        assert_eq!(
+            vec![Action::RotateForwardX],
+            bindings.just_pressed_actions(&input)
+        );
+        assert!(!bindings.is_held(&input, Action::FaceOrigin));
+        assert!(!bindings.is_held(&Input::with_keys(&[Key::X]), Action::RotateForwardX));
+
+        // RightShift works like LeftShift
+        input.update_keys(&[], 0.1);
+        input.update_keys(&[Key::RightShift, Key::G], 0.1);
+        assert!(bindings.just_pressed(&input, Action::FaceAboveOrigin));
+        input.update_keys(&[Key::RightShift], 0.1);
+        assert!(bindings.just_released(&input, Action::FaceAboveOrigin));
+        assert_eq!(None, bindings.held_for(&input, Action::FaceAboveOrigin));
+    }
+
+    #[test]
+    fn taps_between_frames() {
+        let bindings = Bindings::default();
+        let mut input = Input::default();
+
+        // pressed and released before the frame
+        input.press_key(Key::G);
+        input.update_keys(&[], 0.1);
+        assert!(bindings.just_pressed(&input, Action::FaceOrigin));
+        input.update_keys(&[], 0.1);
+        assert!(bindings.just_released(&input, Action::FaceOrigin));
+
+        // released and pressed again while held
+        input.update_keys(&[Key::G], 0.1);
+        input.update_keys(&[Key::G], 0.1);
+        assert_eq!(Some(0.1), bindings.held_for(&input, Action::FaceOrigin));
+        input.press_key(Key::G);
+        input.update_keys(&[Key::G], 0.1);
+        assert!(bindings.just_pressed(&input, Action::FaceOrigin));
+        assert_eq!(Some(0.), bindings.held_for(&input, Action::FaceOrigin));
+    }
+}
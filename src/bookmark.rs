@@ -0,0 +1,120 @@
+use crate::projection::Projection;
+
+use failure::format_err;
+use serde::{Deserialize, Serialize};
+use std::{fs, io::Read, path::Path};
+
+pub const BOOKMARKS: usize = 9;
+
+/// Seconds taken to move the camera to a recalled bookmark
+pub const TRANSITION_TIME: f32 = 1.;
+
+/// Camera pose and zoom
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub struct Bookmark {
+    pub translation: [f32; 3],
+    pub yaw: f32,
+    pub pitch: f32,
+    pub projection: Projection,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, Serialize, PartialEq)]
+pub struct Bookmarks(pub [Option<Bookmark>; BOOKMARKS]);
+
+impl Bookmark {
+    /// Pose at `t` in `[0, 1]` of the way from `self` to `other`, turning the
+    /// short way around. The projection only blends between the same kinds.
+    pub fn lerp(&self, other: &Bookmark, t: f32) -> Bookmark {
+        let mix = |a: f32, b: f32| a + (b - a) * t;
+        let mut translation = [0.; 3];
+        for (i, v) in translation.iter_mut().enumerate() {
+            *v = mix(self.translation[i], other.translation[i]);
+        }
+        let yaw_error = (other.yaw - self.yaw + 180.).rem_euclid(360.) - 180.;
+
+        let projection = match (self.projection, other.projection) {
+            (
+                Projection::Perspective { fov: a, .. },
+                Projection::Perspective { fov: b, near, far },
+            ) => Projection::Perspective {
+                fov: mix(a, b),
+                near,
+                far,
+            },
+            (
+                Projection::Orthographic { height: a, .. },
+                Projection::Orthographic {
+                    height: b,
+                    near,
+                    far,
+                },
+            ) => Projection::Orthographic {
+                height: mix(a, b),
+                near,
+                far,
+            },
+            (_, projection) => projection,
+        };
+
+        Bookmark {
+            translation,
+            yaw: self.yaw + yaw_error * t,
+            pitch: mix(self.pitch, other.pitch),
+            projection,
+        }
+    }
+}
+
+impl Bookmarks {
+    /// Reads the bookmarks in `path`, a missing file has no bookmarks
+    pub fn from_path<P>(path: P) -> Result<Bookmarks, failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut content = String::default();
+        match fs::File::open(path) {
+            Ok(mut file) => file.read_to_string(&mut content)?,
+            Err(ref e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Bookmarks::default());
+            }
+            Err(e) => return Err(e.into()),
+        };
+        serde_json::from_str(&content)
+            .map_err(|e| format_err!("Failed to read bookmarks file: {:#?}", e))
+    }
+
+    pub fn save<P>(&self, path: P) -> Result<(), failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn lerp_turns_short_way() {
+        let a = Bookmark {
+            translation: [0., 0., 0.],
+            yaw: 170.,
+            pitch: 0.,
+            projection: Projection::default(),
+        };
+        let b = Bookmark {
+            translation: [2., 4., 6.],
+            yaw: -170.,
+            pitch: 20.,
+            ..a
+        };
+        let m = a.lerp(&b, 0.5);
+        assert_eq!([1., 2., 3.], m.translation);
+        assert_eq!(180., m.yaw);
+        assert_eq!(10., m.pitch);
+        assert_eq!(b.projection, a.lerp(&b, 1.).projection);
+    }
+}
@@ -0,0 +1,163 @@
+use cgmath::{vec3, vec4, InnerSpace, Matrix, Matrix4, Vector3, Vector4};
+
+/// Axis aligned bounding box
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Aabb {
+    pub min: Vector3<f32>,
+    pub max: Vector3<f32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Sphere {
+    pub center: Vector3<f32>,
+    pub radius: f32,
+}
+
+/// Planes of a view volume, with normals pointing inside
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Frustum {
+    planes: [Vector4<f32>; 6],
+}
+
+impl Aabb {
+    /// Smallest box around `points`, or a box at the origin without points
+    pub fn from_points<I>(points: I) -> Aabb
+    where
+        I: IntoIterator<Item = Vector3<f32>>,
+    {
+        let mut points = points.into_iter();
+        let first = match points.next() {
+            Some(p) => p,
+            None => return Aabb::new(vec3(0., 0., 0.), vec3(0., 0., 0.)),
+        };
+        points.fold(Aabb::new(first, first), |b, p| {
+            Aabb::new(
+                vec3(b.min.x.min(p.x), b.min.y.min(p.y), b.min.z.min(p.z)),
+                vec3(b.max.x.max(p.x), b.max.y.max(p.y), b.max.z.max(p.z)),
+            )
+        })
+    }
+
+    pub fn new(min: Vector3<f32>, max: Vector3<f32>) -> Aabb {
+        Aabb { min, max }
+    }
+
+    pub fn center(&self) -> Vector3<f32> {
+        (self.min + self.max) / 2.
+    }
+
+    /// Box around this one after `m` is applied to it
+    pub fn transformed(&self, m: &Matrix4<f32>) -> Aabb {
+        let center = (m * self.center().extend(1.)).truncate();
+        let half = (self.max - self.min) / 2.;
+        let extent = |row: usize| {
+            let r = m.row(row);
+            r.x.abs() * half.x + r.y.abs() * half.y + r.z.abs() * half.z
+        };
+        let extent = vec3(extent(0), extent(1), extent(2));
+        Aabb::new(center - extent, center + extent)
+    }
+}
+
+impl Sphere {
+    /// Sphere centered on `aabb` that encloses it
+    pub fn around(aabb: &Aabb) -> Sphere {
+        Sphere {
+            center: aabb.center(),
+            radius: (aabb.max - aabb.min).magnitude() / 2.,
+        }
+    }
+
+    /// Sphere around this one after `m` is applied to it, using the largest
+    /// scale of `m`
+    pub fn transformed(&self, m: &Matrix4<f32>) -> Sphere {
+        let scale =
+            m.x.truncate()
+                .magnitude()
+                .max(m.y.truncate().magnitude())
+                .max(m.z.truncate().magnitude());
+        Sphere {
+            center: (m * self.center.extend(1.)).truncate(),
+            radius: self.radius * scale,
+        }
+    }
+}
+
+impl Frustum {
+    /// Extracts the planes of the clip space volume of `m`, usually
+    /// `projection * view`, giving them in world space
+    pub fn from_matrix(m: &Matrix4<f32>) -> Frustum {
+        let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+        let normalize = |p: Vector4<f32>| p / p.truncate().magnitude();
+        Frustum {
+            planes: [
+                normalize(r3 + r0),
+                normalize(r3 - r0),
+                normalize(r3 + r1),
+                normalize(r3 - r1),
+                normalize(r3 + r2),
+                normalize(r3 - r2),
+            ],
+        }
+    }
+
+    pub fn intersects_sphere(&self, sphere: &Sphere) -> bool {
+        self.planes
+            .iter()
+            .all(|p| distance(p, sphere.center) >= -sphere.radius)
+    }
+
+    /// Tests the corner of `aabb` furthest along each plane normal, boxes
+    /// near the frustum corners may pass without being visible
+    pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+        self.planes.iter().all(|p| {
+            let corner = vec3(
+                if p.x >= 0. { aabb.max.x } else { aabb.min.x },
+                if p.y >= 0. { aabb.max.y } else { aabb.min.y },
+                if p.z >= 0. { aabb.max.z } else { aabb.min.z },
+            );
+            distance(p, corner) >= 0.
+        })
+    }
+}
+
+fn distance(plane: &Vector4<f32>, p: Vector3<f32>) -> f32 {
+    plane.dot(vec4(p.x, p.y, p.z, 1.))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{perspective, Deg, Point3};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn transformed_aabb() {
+        let b = Aabb::from_points(vec![vec3(-1., -1., -1.), vec3(1., 2., 1.)]);
+        let m = Matrix4::from_translation(vec3(10., 0., 0.)) * Matrix4::from_scale(2.);
+        assert_eq!(
+            Aabb::new(vec3(8., -2., -2.), vec3(12., 4., 2.)),
+            b.transformed(&m)
+        );
+    }
+
+    #[test]
+    fn frustum_culling() {
+        let view = Matrix4::look_at(
+            Point3::new(0., 0., 10.),
+            Point3::new(0., 0., 0.),
+            vec3(0., 1., 0.),
+        );
+        let frustum = Frustum::from_matrix(&(perspective(Deg(45.), 1., 0.1, 100.) * view));
+        let unit = Aabb::new(vec3(-1., -1., -1.), vec3(1., 1., 1.));
+        let moved = |x, z| unit.transformed(&Matrix4::from_translation(vec3(x, 0., z)));
+
+        assert!(frustum.intersects_aabb(&unit));
+        assert!(frustum.intersects_sphere(&Sphere::around(&unit)));
+        // behind the camera, beyond the far plane and off to the side
+        assert!(!frustum.intersects_aabb(&moved(0., 20.)));
+        assert!(!frustum.intersects_aabb(&moved(0., -200.)));
+        assert!(!frustum.intersects_aabb(&moved(30., 0.)));
+        assert!(!frustum.intersects_sphere(&Sphere::around(&moved(30., 0.))));
+    }
+}
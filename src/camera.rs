@@ -1,76 +1,201 @@
 use crate::{
-    model_pos::{ModelPosition, Movement},
+    bindings::{Action, Bindings},
+    bookmark::{self, Bookmark},
+    camera_path::CameraPath,
+    gamepad::Axis,
+    input::Input,
+    model_pos::{ModelPosition, Movement, Space},
+    projection::Projection,
     scene::SceneObject,
 };
 
-use cgmath::{self, vec3};
+use cgmath::{self, vec3, Deg, InnerSpace, Rotation, Rotation3};
+use serde::Deserialize;
 
 type Point3 = cgmath::Point3<f32>;
 type Vector3 = cgmath::Vector3<f32>;
 type Matrix4 = cgmath::Matrix4<f32>;
 type Quaternion = cgmath::Quaternion<f32>;
 
-const SENSITIVITY: f32 = 0.005;
-const ZOOM: f32 = 45.;
+/// Degrees turned per pixel of mouse movement
+pub const SENSITIVITY: f32 = 0.15;
+/// Fly movement speed in units per second
+pub const SPEED: f32 = 5.;
+const ZOOM_SPEED: f32 = 20.;
+/// Degrees per second turned with a fully deflected stick
+const STICK_TURN_SPEED: f32 = 90.;
+const PITCH_LIMIT: f32 = 89.;
+const PAN_SPEED: f32 = 0.002;
+const DOLLY_SPEED: f32 = 0.1;
+const MIN_DISTANCE: f32 = 0.5;
+const DISTANCE: f32 = 20.;
+const FOLLOW_LOOK_AHEAD: f32 = 5.;
+// Larger steps make the explicit spring integration unstable
+const FOLLOW_MAX_STEP: f32 = 0.1;
 const WORLD_UP: Vector3 = Vector3 {
     x: 0.,
     y: 1.,
     z: 0.,
 };
 
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum CameraMode {
+    /// First person navigation, the mouse turns the camera
+    Fly,
+    /// The camera circles around `focus`, driven by mouse drags
+    Orbit,
+    /// The camera chases the selected model, see `Camera::follow`
+    Follow,
+}
+
+/// Axis aligned views, as found in modelling tools
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub enum View {
+    Front,
+    Side,
+    Top,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MouseDrag {
+    Rotate,
+    Pan,
+}
+
 #[derive(Debug)]
 pub struct Camera {
-    pub zoom: f32,
+    pub name: String,
+    pub mode: CameraMode,
+    pub focus: Vector3,
+    pub distance: f32,
+    velocity: Vector3,
+    angular_velocity: (f32, f32),
+    pub projection: Projection,
     pub sensitivity: f32,
+    pub speed: f32,
+    /// Heading in degrees, 0 looks down -Z and positive values turn left
+    pub yaw: f32,
+    /// Elevation in degrees, positive values look up
+    pub pitch: f32,
     pub model_pos: ModelPosition,
-    pub debug_pressed: bool,
+    pub path: Option<CameraPath>,
+    /// Time along `path`, `None` while the path is not playing
+    path_time: Option<f32>,
+    /// Pose the camera is moving from and to, and the time spent moving
+    transition: Option<(Bookmark, Bookmark, f32)>,
 }
 
 impl Default for Camera {
     fn default() -> Camera {
-        let camera = Camera {
-            zoom: ZOOM,
+        let mut camera = Camera {
+            name: String::from("main"),
+            mode: CameraMode::Fly,
+            focus: vec3(0., 0., 0.),
+            distance: DISTANCE,
+            velocity: vec3(0., 0., 0.),
+            angular_velocity: (0., 0.),
+            projection: Projection::default(),
             sensitivity: SENSITIVITY,
+            speed: SPEED,
+            yaw: 0.,
+            pitch: 0.,
             model_pos: ModelPosition::default(),
-            debug_pressed: false,
+            path: None,
+            path_time: None,
+            transition: None,
         };
+        // slides go along the world axes, the view being the camera's own
+        camera.model_pos.space = Space::World;
         camera
     }
 }
 
 impl SceneObject for Camera {
-    fn process_input(&mut self, window: &glfw::Window, delta_time: f32) {
+    fn process_input(&mut self, input: &Input, bindings: &Bindings, delta_time: f32) {
         let point = vec3(0., 0., 0.);
 
-        process_keys!(
-        window;
-        glfw::Key::Up, glfw::Action::Press =>
+        let (xoffset, yoffset) = input.mouse_delta;
+        if xoffset != 0. || yoffset != 0. {
+            match self.mode {
+                CameraMode::Fly => self.process_mouse_movement(xoffset, yoffset, true),
+                CameraMode::Follow => {}
+                CameraMode::Orbit => {
+                    if input.left_button {
+                        self.process_mouse_drag(xoffset, yoffset, MouseDrag::Rotate);
+                    } else if input.middle_button {
+                        self.process_mouse_drag(xoffset, yoffset, MouseDrag::Pan);
+                    }
+                }
+            }
+        }
+        if input.scroll != 0. {
+            match self.mode {
+                CameraMode::Fly | CameraMode::Follow => self.process_mouse_scroll(input.scroll),
+                CameraMode::Orbit => self.dolly(input.scroll),
+            }
+        }
+
+        // Keys moving the camera carry the orbit focus along
+        let translation = self.model_pos.translation;
+        process_actions!(
+        bindings, input;
+        Action::CameraOrbitUp =>
             self.model_pos.rotate_around(Movement::BackwardX, point, delta_time),
-            self.model_pos.slide(Movement::BackwardZ, delta_time),
-        glfw::Key::Down, glfw::Action::Press =>
+        Action::CameraOrbitDown =>
             self.model_pos.rotate_around(Movement::ForwardX, point, delta_time),
-            self.model_pos.slide(Movement::ForwardZ, delta_time),
-        glfw::Key::Left, glfw::Action::Press =>
+        Action::CameraOrbitLeft =>
             self.model_pos.rotate_around(Movement::BackwardY, point, delta_time),
-            self.model_pos.slide(Movement::BackwardX, delta_time),
-        glfw::Key::Right, glfw::Action::Press =>
+        Action::CameraOrbitRight =>
             self.model_pos.rotate_around(Movement::ForwardY, point, delta_time),
-            self.model_pos.slide(Movement::ForwardX, delta_time)
+        Action::CameraSlideForward => self.model_pos.slide(Movement::BackwardZ, delta_time),
+        Action::CameraSlideBackward => self.model_pos.slide(Movement::ForwardZ, delta_time),
+        Action::CameraSlideLeft => self.model_pos.slide(Movement::BackwardX, delta_time),
+        Action::CameraSlideRight => self.model_pos.slide(Movement::ForwardX, delta_time),
         );
 
-        process_keys!(
-        window;
-        glfw::Key::I, glfw::Action::Press => self.model_pos.orientation.s += 0.05,
-        glfw::Key::O, glfw::Action::Press => self.model_pos.orientation.s -= 0.05,
-        glfw::Key::K, glfw::Action::Press => self.model_pos.look_at(point, WORLD_UP, delta_time),
-        glfw::Key::J, glfw::Action::Release => self.debug_pressed = false,
-        glfw::Key::J, glfw::Action::Press => {
-            if self.debug_pressed == false {
-                self.debug_pressed = true;
-                println!("Model_pos: {:#?}", self);
-                println!("Delta time: {:#?}", delta_time);
+        // Fly controls, relative to where the camera is looking
+        let (front, right) = (self.front(), self.right());
+        process_actions!(
+        bindings, input;
+        Action::FlyForward => self.fly(front, delta_time),
+        Action::FlyBackward => self.fly(-front, delta_time),
+        Action::FlyLeft => self.fly(-right, delta_time),
+        Action::FlyRight => self.fly(right, delta_time),
+        Action::FlyUp => self.fly(WORLD_UP, delta_time),
+        Action::FlyDown => self.fly(-WORLD_UP, delta_time),
+        );
+        self.fly(front, input.axis(Axis::CameraForward) * delta_time);
+        self.fly(right, input.axis(Axis::CameraRight) * delta_time);
+        if self.mode == CameraMode::Orbit {
+            self.focus += self.model_pos.translation - translation;
+        }
+
+        // Sticks turn like the mouse does, proportionally to the deflection
+        let turn = STICK_TURN_SPEED * delta_time / self.sensitivity;
+        let yaw = input.axis(Axis::CameraYaw) * turn;
+        let pitch = input.axis(Axis::CameraPitch) * turn;
+        if yaw != 0. || pitch != 0. {
+            match self.mode {
+                CameraMode::Fly => self.process_mouse_movement(yaw, pitch, true),
+                CameraMode::Follow => {}
+                CameraMode::Orbit => self.process_mouse_drag(yaw, pitch, MouseDrag::Rotate),
             }
-        });
+        }
+
+        process_actions!(
+        bindings, input;
+        Action::ZoomIn => self.process_mouse_scroll(ZOOM_SPEED * delta_time),
+        Action::ZoomOut => self.process_mouse_scroll(-ZOOM_SPEED * delta_time),
+        Action::LookAtOrigin => self.look_at(point),
+        Action::ViewFront => self.set_view(View::Front),
+        Action::ViewSide => self.set_view(View::Side),
+        Action::ViewTop => self.set_view(View::Top),
+        );
+
+        if bindings.just_pressed(input, Action::DebugCamera) {
+            println!("Model_pos: {:#?}", self);
+            println!("Delta time: {:#?}", delta_time);
+        }
     }
 }
 
@@ -83,38 +208,385 @@ impl Camera {
         rmat * tmat
     }
 
-    pub fn process_mouse_movement(&mut self, xoffset: f32, yoffset: f32, constrain_pitch: bool) {
-        self.model_pos
-            .rotate(Movement::ForwardX, -yoffset * self.sensitivity);
-        self.model_pos
-            .rotate(Movement::ForwardY, xoffset * self.sensitivity);
+    /// Direction the camera is looking at, in world space
+    pub fn front(&self) -> Vector3 {
+        let (yaw, pitch) = (self.yaw.to_radians(), self.pitch.to_radians());
+        vec3(
+            -yaw.sin() * pitch.cos(),
+            pitch.sin(),
+            -yaw.cos() * pitch.cos(),
+        )
+    }
+
+    /// Horizontal right vector, only depends on yaw so it stays valid when
+    /// looking straight up or down
+    pub fn right(&self) -> Vector3 {
+        let yaw = self.yaw.to_radians();
+        vec3(yaw.cos(), 0., -yaw.sin())
+    }
+
+    pub fn projection_matrix(&self, aspect: f32) -> Matrix4 {
+        self.projection.matrix(aspect)
+    }
+
+    /// Switches between perspective and orthographic projections, keeping
+    /// objects at the focus distance the same size on screen
+    pub fn toggle_projection(&mut self) {
+        self.projection = self.projection.toggled(self.distance);
+    }
+
+    /// Looks at the focus point along one of the world axes, using an
+    /// orthographic projection
+    pub fn set_view(&mut self, view: View) {
+        if let Projection::Perspective { .. } = self.projection {
+            self.toggle_projection();
+        }
+        let (yaw, pitch) = match view {
+            View::Front => (0., 0.),
+            View::Side => (90., 0.),
+            View::Top => (0., -90.),
+        };
+        self.yaw = yaw;
+        self.pitch = pitch;
+        self.update_orientation();
+        self.update_orbit_position();
+    }
+
+    pub fn fly(&mut self, direction: Vector3, delta_time: f32) {
+        self.model_pos.translation += direction * self.speed * delta_time;
+    }
 
-        // Ensure z orientation dones't get messedup by normalization error;
-        self.model_pos.orientation.v.z = 0.;
+    /// Turns the camera so it faces `p`
+    pub fn look_at(&mut self, p: Vector3) {
+        let dir = p - self.model_pos.translation;
+        if dir.magnitude2() == 0. {
+            return;
+        }
+        let dir = dir.normalize();
+        self.yaw = (-dir.x).atan2(-dir.z).to_degrees();
+        self.pitch = dir.y.asin().to_degrees();
+        self.clamp_pitch();
+        self.update_orientation();
+    }
+
+    pub fn process_mouse_movement(&mut self, xoffset: f32, yoffset: f32, constrain_pitch: bool) {
+        self.yaw -= xoffset * self.sensitivity;
+        self.pitch += yoffset * self.sensitivity;
 
         // Make sure that when pitch is out of bounds, screen doesn't get flipped
         if constrain_pitch {
-            // FIXME constrain quaternion to 90º
-            // if self.pitch > 89. {
-            //     self.pitch = 89.;
-            // }
-            // if self.pitch < -89. {
-            //     self.pitch = -89.;
-            // }
+            self.clamp_pitch();
+        }
+        self.update_orientation();
+    }
+
+    /// Switches between fly and orbit modes without moving the camera, the
+    /// orbit focus is placed in front of the camera at the current distance.
+    /// A following camera goes back to fly mode.
+    pub fn toggle_mode(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Fly => {
+                let depth = (self.focus - self.model_pos.translation).dot(self.front());
+                if depth > MIN_DISTANCE {
+                    self.distance = depth;
+                }
+                self.focus = self.model_pos.translation + self.front() * self.distance;
+                CameraMode::Orbit
+            }
+            CameraMode::Orbit | CameraMode::Follow => CameraMode::Fly,
+        };
+    }
+
+    /// Starts following the selected model, or stops in fly mode
+    pub fn toggle_follow(&mut self) {
+        self.mode = match self.mode {
+            CameraMode::Follow => CameraMode::Fly,
+            _ => {
+                self.velocity = vec3(0., 0., 0.);
+                self.angular_velocity = (0., 0.);
+                CameraMode::Follow
+            }
+        };
+    }
+
+    /// Moves the camera towards `target.config.camera_offset`, given in the
+    /// target's local frame, looking slightly ahead of the target.
+    ///
+    /// Position, yaw and pitch are each driven by a critically damped spring
+    /// with `target.config.camera_stiffness`.
+    pub fn follow(&mut self, target: &ModelPosition, delta_time: f32) {
+        let dt = delta_time.min(FOLLOW_MAX_STEP);
+        let stiffness = target.config.camera_stiffness;
+        let damping = 2. * stiffness.sqrt();
+
+        let offset = Vector3::from(target.config.camera_offset);
+        let goal = target.translation + target.orientation.rotate_vector(offset);
+        let accel = (goal - self.model_pos.translation) * stiffness - self.velocity * damping;
+        self.velocity += accel * dt;
+        self.model_pos.translation += self.velocity * dt;
+
+        // Models face their local -Z, the same convention as the camera
+        let forward = target.orientation.rotate_vector(vec3(0., 0., -1.));
+        let dir = target.translation + forward * FOLLOW_LOOK_AHEAD - goal;
+        if dir.magnitude2() > 0. {
+            let dir = dir.normalize();
+            let goal_yaw = (-dir.x).atan2(-dir.z).to_degrees();
+            let goal_pitch = dir.y.asin().to_degrees();
+
+            // Wrap the yaw error so the camera turns the short way around
+            let yaw_error = (goal_yaw - self.yaw + 180.).rem_euclid(360.) - 180.;
+            let pitch_error = goal_pitch - self.pitch;
+            let (yaw_velocity, pitch_velocity) = self.angular_velocity;
+            let yaw_velocity = yaw_velocity + (yaw_error * stiffness - yaw_velocity * damping) * dt;
+            let pitch_velocity =
+                pitch_velocity + (pitch_error * stiffness - pitch_velocity * damping) * dt;
+            self.angular_velocity = (yaw_velocity, pitch_velocity);
+            self.yaw += yaw_velocity * dt;
+            self.pitch += pitch_velocity * dt;
+        }
+
+        self.clamp_pitch();
+        self.update_orientation();
+    }
+
+    /// Starts the path from the beginning, or stops it if it is playing
+    pub fn toggle_path(&mut self) {
+        self.path_time = match (&self.path, self.path_time) {
+            (Some(_), None) => Some(0.),
+            _ => None,
+        };
+    }
+
+    pub fn is_playing_path(&self) -> bool {
+        self.path_time.is_some()
+    }
+
+    /// Advances the path, placing the camera on it and looking at its target
+    pub fn update_path(&mut self, delta_time: f32) {
+        let (path, t) = match (&self.path, self.path_time) {
+            (Some(path), Some(t)) => (path, t + delta_time),
+            _ => return,
+        };
+        let t = if path.looping && path.duration > 0. {
+            t % path.duration
+        } else {
+            t
+        };
+        let (position, target) = path.sample(t);
+        self.path_time = if t < path.duration || path.looping {
+            Some(t)
+        } else {
+            None
+        };
+
+        self.model_pos.translation = position;
+        self.look_at(target);
+    }
+
+    pub fn bookmark(&self) -> Bookmark {
+        Bookmark {
+            translation: self.model_pos.translation.into(),
+            yaw: self.yaw,
+            pitch: self.pitch,
+            projection: self.projection,
+        }
+    }
+
+    /// Starts moving the camera to `bookmark`
+    pub fn recall(&mut self, bookmark: Bookmark) {
+        self.path_time = None;
+        self.transition = Some((self.bookmark(), bookmark, 0.));
+    }
+
+    /// Advances the transition started by `recall`, easing in and out
+    pub fn update_transition(&mut self, delta_time: f32) {
+        let (from, to, t) = match self.transition {
+            Some((from, to, t)) => (from, to, t + delta_time),
+            None => return,
+        };
+        let s = (t / bookmark::TRANSITION_TIME).min(1.);
+        self.set_pose(&from.lerp(&to, s * s * (3. - 2. * s)));
+        self.transition = if s < 1. { Some((from, to, t)) } else { None };
+    }
+
+    fn set_pose(&mut self, pose: &Bookmark) {
+        self.model_pos.translation = pose.translation.into();
+        self.yaw = pose.yaw;
+        self.pitch = pose.pitch;
+        self.projection = pose.projection;
+        self.clamp_pitch();
+        self.update_orientation();
+        if self.mode == CameraMode::Orbit {
+            self.focus = self.model_pos.translation + self.front() * self.distance;
+        }
+    }
+
+    /// Point the camera is centered on, in front of it at the focus distance
+    /// when it isn't orbiting
+    pub fn focus_point(&self) -> Vector3 {
+        match self.mode {
+            CameraMode::Orbit => self.focus,
+            CameraMode::Fly | CameraMode::Follow => {
+                self.model_pos.translation + self.front() * self.distance
+            }
+        }
+    }
+
+    /// Centers the orbit on `p`, keeping the viewing direction and distance
+    pub fn set_focus(&mut self, p: Vector3) {
+        self.focus = p;
+        if self.mode == CameraMode::Orbit {
+            self.update_orbit_position();
+        } else {
+            self.look_at(p);
+            self.distance = (p - self.model_pos.translation)
+                .magnitude()
+                .max(MIN_DISTANCE);
+        }
+    }
+
+    /// Handles mouse movement while a button is held in orbit mode
+    pub fn process_mouse_drag(&mut self, xoffset: f32, yoffset: f32, drag: MouseDrag) {
+        match drag {
+            MouseDrag::Rotate => {
+                self.process_mouse_movement(xoffset, yoffset, true);
+            }
+            MouseDrag::Pan => {
+                let right = self.right();
+                let up = right.cross(self.front());
+                let scale = PAN_SPEED * self.distance;
+                self.focus -= right * xoffset * scale + up * yoffset * scale;
+            }
+        }
+        self.update_orbit_position();
+    }
+
+    /// Moves the camera towards or away from the orbit focus
+    pub fn dolly(&mut self, yoffset: f32) {
+        self.distance = (self.distance * (1. - yoffset * DOLLY_SPEED)).max(MIN_DISTANCE);
+        // Moving an orthographic camera doesn't change the image size
+        if let Projection::Orthographic { .. } = self.projection {
+            self.projection.zoom(yoffset);
         }
+        self.update_orbit_position();
+    }
+
+    fn update_orbit_position(&mut self) {
+        self.model_pos.translation = self.focus - self.front() * self.distance;
     }
 
     // Processes input received from a mouse scroll-wheel event. Only requires input
     // on the vertical wheel-axis
     pub fn process_mouse_scroll(&mut self, yoffset: f32) {
-        if self.zoom >= 1. && self.zoom <= 45. {
-            self.zoom -= yoffset;
-        }
-        if self.zoom <= 1. {
-            self.zoom = 1.;
-        }
-        if self.zoom >= 45. {
-            self.zoom = 45.;
+        self.projection.zoom(yoffset);
+    }
+
+    fn clamp_pitch(&mut self) {
+        self.pitch = self.pitch.max(-PITCH_LIMIT).min(PITCH_LIMIT);
+    }
+
+    /// Rebuilds the view rotation from yaw and pitch, so no roll can build up
+    fn update_orientation(&mut self) {
+        self.model_pos.orientation =
+            Quaternion::from_angle_x(Deg(-self.pitch)) * Quaternion::from_angle_y(Deg(-self.yaw));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::Rotation;
+    use glfw::Key;
+
+    #[test]
+    fn pitch_is_clamped() {
+        let mut camera = Camera::default();
+        camera.process_mouse_movement(0., 10_000., true);
+        assert_eq!(PITCH_LIMIT, camera.pitch);
+        camera.process_mouse_movement(0., -20_000., true);
+        assert_eq!(-PITCH_LIMIT, camera.pitch);
+    }
+
+    #[test]
+    fn synthetic_input() {
+        let bindings = Bindings::default();
+        let mut camera = Camera::default();
+        camera.process_input(&Input::with_keys(&[Key::Kp8]), &bindings, 0.5);
+        assert!((camera.model_pos.translation - vec3(0., 0., -SPEED * 0.5)).magnitude() < 1e-5);
+
+        let mut input = Input::default();
+        input.mouse_delta = (10., 0.);
+        camera.process_input(&input, &bindings, 0.);
+        assert_eq!(-10. * SENSITIVITY, camera.yaw);
+
+        // sliding keeps to the world axes once turned
+        let position = camera.model_pos.translation;
+        let right = Input::with_keys(&[Key::LeftShift, Key::Right]);
+        camera.process_input(&right, &bindings, 0.5);
+        let moved = camera.model_pos.translation - position;
+        let expected = vec3(camera.model_pos.config.base_speed * 0.5, 0., 0.);
+        assert!((moved - expected).magnitude() < 1e-5);
+
+        // orbiting needs a mouse button held down
+        camera.toggle_mode();
+        let yaw = camera.yaw;
+        camera.process_input(&input, &bindings, 0.);
+        assert_eq!(yaw, camera.yaw);
+        input.left_button = true;
+        camera.process_input(&input, &bindings, 0.);
+        assert_eq!(yaw - 10. * SENSITIVITY, camera.yaw);
+
+        // flying carries the orbit focus along
+        let (position, focus) = (camera.model_pos.translation, camera.focus);
+        camera.process_input(&Input::with_keys(&[Key::Kp8]), &bindings, 0.5);
+        let moved = camera.model_pos.translation - position;
+        assert!(moved.magnitude() > 1.);
+        assert!((camera.focus - focus - moved).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn front_matches_view() {
+        let mut camera = Camera::default();
+        camera.process_mouse_movement(120., -40., true);
+        // The view rotation maps the front vector onto -Z
+        let v = camera.model_pos.orientation.rotate_vector(camera.front());
+        assert!((v - vec3(0., 0., -1.)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn toggle_mode_keeps_view() {
+        let mut camera = Camera::default();
+        camera.model_pos.translation = vec3(0., 1., 20.);
+        camera.process_mouse_movement(30., 10., true);
+        let (position, orientation) = (camera.model_pos.translation, camera.model_pos.orientation);
+
+        camera.toggle_mode();
+        assert_eq!(CameraMode::Orbit, camera.mode);
+        camera.dolly(0.);
+        assert!((camera.model_pos.translation - position).magnitude() < 1e-4);
+        assert_eq!(orientation, camera.model_pos.orientation);
+
+        camera.toggle_mode();
+        assert_eq!(CameraMode::Fly, camera.mode);
+        camera.toggle_follow();
+        assert_eq!(CameraMode::Follow, camera.mode);
+        camera.toggle_mode();
+        assert_eq!(CameraMode::Fly, camera.mode);
+    }
+
+    #[test]
+    fn follow_settles_behind_target() {
+        let mut camera = Camera::default();
+        let mut target = ModelPosition::default();
+        target.translation = vec3(3., 0., -2.);
+        target.orientation = Quaternion::from_angle_y(Deg(90.));
+
+        for _ in 0..600 {
+            camera.follow(&target, 1. / 60.);
         }
+        let offset = Vector3::from(target.config.camera_offset);
+        let goal = target.translation + target.orientation.rotate_vector(offset);
+        assert!((camera.model_pos.translation - goal).magnitude() < 1e-2);
+        assert!((camera.yaw - 90.).abs() < 0.5);
     }
 }
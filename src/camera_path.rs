@@ -0,0 +1,156 @@
+use cgmath::Vector3;
+use failure::{ensure, format_err};
+use serde::Deserialize;
+use splines::{Interpolation, Key, Spline};
+use std::{fs, io::Read, path::Path};
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub enum PathInterpolation {
+    Linear,
+    CatmullRom,
+}
+
+impl Default for PathInterpolation {
+    fn default() -> Self {
+        PathInterpolation::CatmullRom
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct PathKey {
+    pub t: f32,
+    pub value: [f32; 3],
+}
+
+/// Camera path as written in JSON, the camera moves through `position`
+/// while looking at the point moving through `target`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct PathConfig {
+    pub position: Vec<PathKey>,
+    pub target: Vec<PathKey>,
+    #[serde(default)]
+    pub interpolation: PathInterpolation,
+    #[serde(default)]
+    pub looping: bool,
+}
+
+/// Spline with the keys at its ends, used outside of the sampled range
+#[derive(Clone, Debug)]
+struct Track {
+    spline: Spline<Vector3<f32>>,
+    first: (f32, Vector3<f32>),
+    last: (f32, Vector3<f32>),
+}
+
+#[derive(Clone, Debug)]
+pub struct CameraPath {
+    position: Track,
+    target: Track,
+    pub duration: f32,
+    pub looping: bool,
+}
+
+impl CameraPath {
+    pub fn new(config: PathConfig) -> Result<CameraPath, failure::Error> {
+        ensure!(
+            !config.position.is_empty() && !config.target.is_empty(),
+            "Camera path needs at least one position and one target key"
+        );
+        let interpolation = match config.interpolation {
+            PathInterpolation::Linear => Interpolation::Linear,
+            PathInterpolation::CatmullRom => Interpolation::CatmullRom,
+        };
+        let position = Track::new(config.position, interpolation);
+        let target = Track::new(config.target, interpolation);
+
+        Ok(CameraPath {
+            duration: position.last.0.max(target.last.0),
+            position,
+            target,
+            looping: config.looping,
+        })
+    }
+
+    pub fn from_path<P>(path: P) -> Result<CameraPath, failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut content = String::default();
+        fs::File::open(path)?.read_to_string(&mut content)?;
+        let config = serde_json::from_str(&content)
+            .map_err(|e| format_err!("Failed to read camera path file: {:#?}", e))?;
+        CameraPath::new(config)
+    }
+
+    /// Camera position and look at target at time `t`
+    pub fn sample(&self, t: f32) -> (Vector3<f32>, Vector3<f32>) {
+        (self.position.sample(t), self.target.sample(t))
+    }
+}
+
+impl Track {
+    /// The first and last keys are repeated, like in `model_pos::curve`, so
+    /// Catmull-Rom has neighbours to sample the whole range
+    fn new(mut keys: Vec<PathKey>, interpolation: Interpolation) -> Track {
+        keys.sort_by(|a, b| a.t.partial_cmp(&b.t).unwrap());
+        let first = (keys[0].t, keys[0].value.into());
+        let last = (keys[keys.len() - 1].t, keys[keys.len() - 1].value.into());
+
+        let mut vec = vec![Key::new(first.0 - 1., first.1, interpolation)];
+        vec.extend(
+            keys.into_iter()
+                .map(|k| Key::new(k.t, k.value.into(), interpolation)),
+        );
+        vec.push(Key::new(last.0 + 1., last.1, interpolation));
+
+        Track {
+            spline: Spline::from_vec(vec),
+            first,
+            last,
+        }
+    }
+
+    fn sample(&self, t: f32) -> Vector3<f32> {
+        if t <= self.first.0 {
+            return self.first.1;
+        }
+        if t >= self.last.0 {
+            return self.last.1;
+        }
+        self.spline.sample(t).unwrap_or(self.last.1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{vec3, InnerSpace};
+    use serde_json::json;
+
+    #[test]
+    fn samples_whole_range() {
+        let config: PathConfig = serde_json::from_value(json!({
+            "position": [
+                { "t": 0.0, "value": [0.0, 1.0, 20.0] },
+                { "t": 2.0, "value": [10.0, 5.0, 0.0] },
+                { "t": 4.0, "value": [0.0, 1.0, -20.0] },
+            ],
+            "target": [
+                { "t": 0.0, "value": [0.0, 0.0, 0.0] },
+            ],
+        }))
+        .unwrap();
+        let path = CameraPath::new(config).unwrap();
+        assert_eq!(4., path.duration);
+
+        let (p, t) = path.sample(-1.);
+        assert!((p - vec3(0., 1., 20.)).magnitude() < 1e-5);
+        assert!((t - vec3(0., 0., 0.)).magnitude() < 1e-5);
+
+        let (p, _) = path.sample(2.);
+        assert!((p - vec3(10., 5., 0.)).magnitude() < 1e-4);
+
+        let (p, _) = path.sample(10.);
+        assert!((p - vec3(0., 1., -20.)).magnitude() < 1e-5);
+    }
+}
@@ -0,0 +1,138 @@
+use failure::ensure;
+use std::{
+    fs,
+    io::{BufWriter, Write},
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Where the frames of a recording go
+enum Sink {
+    /// Numbered PNG files in a directory
+    Images(PathBuf),
+    /// Uncompressed YUV4MPEG2 stream, as read by ffmpeg and most players
+    Y4m(BufWriter<fs::File>),
+}
+
+/// Writes every frame given to it, the simulation should advance exactly
+/// `1 / fps` seconds between frames
+pub struct Recording {
+    sink: Sink,
+    pub fps: f32,
+    frame: usize,
+    size: Option<(u32, u32)>,
+}
+
+impl Recording {
+    /// Records into a `.y4m` file, or into a directory of images for other
+    /// paths
+    pub fn new<P>(path: P, fps: f32) -> Result<Recording, failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        ensure!(fps > 0., "Recording frame rate should be positive");
+        let sink = if path.extension().map_or(false, |e| e == "y4m") {
+            Sink::Y4m(BufWriter::new(fs::File::create(path)?))
+        } else {
+            fs::create_dir_all(path)?;
+            Sink::Images(path.to_owned())
+        };
+        Ok(Recording {
+            sink,
+            fps,
+            frame: 0,
+            size: None,
+        })
+    }
+
+    /// Saves a frame of RGBA pixels, starting at the top left corner
+    pub fn write_frame(
+        &mut self,
+        pixels: &[u8],
+        width: u32,
+        height: u32,
+    ) -> Result<(), failure::Error> {
+        match &mut self.sink {
+            Sink::Images(dir) => {
+                let path = dir.join(format!("frame-{:05}.png", self.frame));
+                image::save_buffer(path, pixels, width, height, image::ColorType::RGBA(8))?;
+            }
+            Sink::Y4m(file) => {
+                match self.size {
+                    None => write_y4m_header(file, width, height, self.fps)?,
+                    Some(size) => ensure!(
+                        size == (width, height),
+                        "Y4M recordings can't change size, stop recording before resizing"
+                    ),
+                }
+                write_y4m_frame(file, pixels)?;
+            }
+        }
+        self.size = Some((width, height));
+        self.frame += 1;
+        Ok(())
+    }
+
+    pub fn frames(&self) -> usize {
+        self.frame
+    }
+}
+
+/// Saves RGBA pixels to `screenshot-<milliseconds since epoch>.png`,
+/// returning the file name
+pub fn screenshot(pixels: &[u8], width: u32, height: u32) -> Result<PathBuf, failure::Error> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH)?;
+    let millis = time.as_secs() * 1000 + u64::from(time.subsec_millis());
+    let path = PathBuf::from(format!("screenshot-{}.png", millis));
+    image::save_buffer(&path, pixels, width, height, image::ColorType::RGBA(8))?;
+    Ok(path)
+}
+
+fn write_y4m_header<W: Write>(w: &mut W, width: u32, height: u32, fps: f32) -> std::io::Result<()> {
+    // frame rates are written as a fraction, keep three decimal places
+    let rate = (fps * 1000.).round() as u32;
+    writeln!(
+        w,
+        "YUV4MPEG2 W{} H{} F{}:1000 Ip A1:1 C444",
+        width, height, rate
+    )
+}
+
+/// Writes the three full resolution planes of a 4:4:4 frame
+fn write_y4m_frame<W: Write>(w: &mut W, pixels: &[u8]) -> std::io::Result<()> {
+    let yuv: Vec<_> = pixels.chunks(4).map(rgb_to_yuv).collect();
+    w.write_all(b"FRAME\n")?;
+    for plane in 0..3 {
+        let bytes: Vec<_> = yuv.iter().map(|p| p[plane]).collect();
+        w.write_all(&bytes)?;
+    }
+    Ok(())
+}
+
+/// BT.601 limited range conversion, the default of Y4M readers
+fn rgb_to_yuv(p: &[u8]) -> [u8; 3] {
+    let (r, g, b) = (f32::from(p[0]), f32::from(p[1]), f32::from(p[2]));
+    let y = 16. + (65.738 * r + 129.057 * g + 25.064 * b) / 256.;
+    let u = 128. + (-37.945 * r - 74.494 * g + 112.439 * b) / 256.;
+    let v = 128. + (112.439 * r - 94.154 * g - 18.285 * b) / 256.;
+    [y.round() as u8, u.round() as u8, v.round() as u8]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn y4m_stream() {
+        let mut out = Vec::new();
+        write_y4m_header(&mut out, 2, 1, 30.).unwrap();
+        write_y4m_frame(&mut out, &[255, 255, 255, 255, 0, 0, 0, 255]).unwrap();
+
+        let mut expected = b"YUV4MPEG2 W2 H1 F30000:1000 Ip A1:1 C444\n".to_vec();
+        expected.extend_from_slice(b"FRAME\n");
+        expected.extend_from_slice(&[235, 16, 128, 128, 128, 128]);
+        assert_eq!(expected, out);
+    }
+}
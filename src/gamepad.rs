@@ -0,0 +1,258 @@
+use crate::bindings::Action;
+
+use failure::{ensure, format_err};
+use glfw::{self, JoystickId};
+use serde::Deserialize;
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    io::Read,
+    path::Path,
+};
+
+/// What an analog axis controls, speeds grow with the stick deflection
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum Axis {
+    /// Slides the selected models along their transform space axes
+    ModelX,
+    ModelY,
+    ModelZ,
+    /// Turns the active camera, or orbits it in orbit mode
+    CameraYaw,
+    CameraPitch,
+    /// Flies the active camera where it's looking
+    CameraForward,
+    CameraRight,
+    /// Triggers scaling the selected models, going from -1 when released to
+    /// 1 when fully pressed. They're ignored until they move, as some
+    /// drivers report 0 before the first press.
+    ScaleUp,
+    ScaleDown,
+}
+
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub struct AxisBinding {
+    pub axis: Axis,
+    #[serde(default)]
+    pub inverted: bool,
+}
+
+/// Axes and buttons numbered like GLFW reports them for the device, which
+/// depends on the platform. The default is an Xbox controller with XInput
+/// on Windows, `configs/gamepad_linux.json` is the same controller with the
+/// xpad driver on Linux.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct GamepadConfig {
+    /// Joystick used, 0 is the first one connected
+    pub joystick: usize,
+    /// Deflection ignored around the center of the sticks, from 0 to 1
+    pub dead_zone: f32,
+    pub axes: BTreeMap<usize, AxisBinding>,
+    /// Action names, as in the key bindings file
+    pub buttons: BTreeMap<usize, String>,
+}
+
+/// Axes and actions of the gamepad in a frame
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct GamepadState {
+    /// Deflection from -1 to 1 without the dead zone, 0 to 1 for triggers
+    pub axes: BTreeMap<Axis, f32>,
+    /// Actions of the buttons held down
+    pub actions: BTreeSet<Action>,
+}
+
+pub struct Gamepad {
+    joystick: JoystickId,
+    dead_zone: f32,
+    axes: BTreeMap<usize, AxisBinding>,
+    buttons: BTreeMap<usize, Action>,
+    /// Trigger axes seen away from 0 since the joystick was connected
+    moved: BTreeSet<usize>,
+}
+
+impl Default for GamepadConfig {
+    fn default() -> Self {
+        let axis = |axis, inverted| AxisBinding { axis, inverted };
+        let axes = vec![
+            (0, axis(Axis::ModelX, false)),
+            (1, axis(Axis::ModelZ, false)),
+            (2, axis(Axis::CameraYaw, false)),
+            (3, axis(Axis::CameraPitch, true)),
+            (4, axis(Axis::ScaleDown, false)),
+            (5, axis(Axis::ScaleUp, false)),
+        ];
+        let buttons = vec![
+            (0, "start_animation"),
+            (4, "select_previous"),
+            (5, "select_next"),
+            (6, "toggle_camera_mode"),
+            (7, "toggle_pause"),
+        ];
+        GamepadConfig {
+            joystick: 0,
+            dead_zone: 0.15,
+            axes: axes.into_iter().collect(),
+            buttons: buttons.into_iter().map(|(b, a)| (b, a.into())).collect(),
+        }
+    }
+}
+
+impl GamepadConfig {
+    pub fn from_path<P>(path: P) -> Result<GamepadConfig, failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut content = String::default();
+        fs::File::open(path)?.read_to_string(&mut content)?;
+        serde_json::from_str(&content)
+            .map_err(|e| format_err!("Failed to read gamepad file: {:#?}", e))
+    }
+}
+
+impl GamepadState {
+    /// Deflection of `axis`, 0 when it isn't mapped
+    pub fn axis(&self, axis: Axis) -> f32 {
+        self.axes.get(&axis).cloned().unwrap_or(0.)
+    }
+}
+
+impl Gamepad {
+    pub fn new(config: GamepadConfig) -> Result<Gamepad, failure::Error> {
+        ensure!(
+            config.dead_zone >= 0. && config.dead_zone < 1.,
+            "Gamepad dead zone should be between 0 and 1"
+        );
+        let joystick = *JOYSTICKS
+            .get(config.joystick)
+            .ok_or_else(|| format_err!("GLFW has {} joysticks", JOYSTICKS.len()))?;
+        let buttons = config
+            .buttons
+            .iter()
+            .map(|(&b, name)| {
+                let action = Action::from_name(name)
+                    .ok_or_else(|| format_err!("Unknown action {:?} in gamepad file", name))?;
+                Ok((b, action))
+            })
+            .collect::<Result<_, failure::Error>>()?;
+        Ok(Gamepad {
+            joystick,
+            dead_zone: config.dead_zone,
+            axes: config.axes,
+            buttons,
+            moved: BTreeSet::new(),
+        })
+    }
+
+    /// State of the joystick, empty when it isn't connected
+    pub fn poll(&mut self, glfw: &glfw::Glfw) -> GamepadState {
+        let joystick = glfw.get_joystick(self.joystick);
+        if !joystick.is_present() {
+            self.moved.clear();
+            return GamepadState::default();
+        }
+        self.read(&joystick.get_axes(), &joystick.get_buttons())
+    }
+
+    /// Maps the raw axes and buttons given by GLFW
+    pub fn read(&mut self, axes: &[f32], buttons: &[i32]) -> GamepadState {
+        let mut state = GamepadState::default();
+        for (&i, binding) in &self.axes {
+            let value = match axes.get(i) {
+                Some(&v) if binding.inverted => -v,
+                Some(&v) => v,
+                None => continue,
+            };
+            let value = match binding.axis {
+                Axis::ScaleUp | Axis::ScaleDown => {
+                    // a trigger at 0 would read as half pressed
+                    if value != 0. {
+                        self.moved.insert(i);
+                    } else if !self.moved.contains(&i) {
+                        continue;
+                    }
+                    apply_dead_zone((value + 1.) / 2., self.dead_zone)
+                }
+                _ => apply_dead_zone(value, self.dead_zone),
+            };
+            if value != 0. {
+                state.axes.insert(binding.axis, value);
+            }
+        }
+        for (&i, &action) in &self.buttons {
+            if buttons.get(i) == Some(&(glfw::Action::Press as i32)) {
+                state.actions.insert(action);
+            }
+        }
+        state
+    }
+}
+
+/// Zero inside the dead zone, growing from 0 at its edge to 1 at full
+/// deflection so small movements stay precise
+pub fn apply_dead_zone(value: f32, dead_zone: f32) -> f32 {
+    let magnitude = value.abs().min(1.);
+    if magnitude <= dead_zone {
+        0.
+    } else {
+        value.signum() * (magnitude - dead_zone) / (1. - dead_zone)
+    }
+}
+
+const JOYSTICKS: &[JoystickId] = &[
+    JoystickId::Joystick1,
+    JoystickId::Joystick2,
+    JoystickId::Joystick3,
+    JoystickId::Joystick4,
+    JoystickId::Joystick5,
+    JoystickId::Joystick6,
+    JoystickId::Joystick7,
+    JoystickId::Joystick8,
+    JoystickId::Joystick9,
+    JoystickId::Joystick10,
+    JoystickId::Joystick11,
+    JoystickId::Joystick12,
+    JoystickId::Joystick13,
+    JoystickId::Joystick14,
+    JoystickId::Joystick15,
+    JoystickId::Joystick16,
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn read_mapped_state() {
+        assert_eq!(0., apply_dead_zone(-0.25, 0.5));
+        assert_eq!(-0.5, apply_dead_zone(-0.75, 0.5));
+        assert_eq!(1., apply_dead_zone(1.25, 0.5));
+
+        let mut gamepad = Gamepad::new(GamepadConfig::default()).unwrap();
+        let press = glfw::Action::Press as i32;
+        // Triggers at 0 aren't pressed until they were seen released
+        let rest = [0.; 6];
+        assert_eq!(GamepadState::default(), gamepad.read(&rest, &[]));
+        gamepad.read(&[0., 0., 0., 0., -1., -1.], &[]);
+
+        let state = gamepad.read(&[1., 0.1, 0., -1., -1., 0.], &[press, 0, 0, 0, 0, press]);
+        let mut expected = GamepadState::default();
+        expected.axes.insert(Axis::ModelX, 1.);
+        expected.axes.insert(Axis::CameraPitch, 1.);
+        expected
+            .axes
+            .insert(Axis::ScaleUp, (0.5 - 0.15) / (1. - 0.15));
+        expected.actions.insert(Action::StartAnimation);
+        expected.actions.insert(Action::SelectNext);
+        assert_eq!(expected, state);
+
+        let config: GamepadConfig = serde_json::from_value(json!({
+            "buttons": { "2": "jump" },
+        }))
+        .unwrap();
+        assert!(Gamepad::new(config).is_err());
+    }
+}
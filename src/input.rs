@@ -0,0 +1,169 @@
+use crate::{
+    bindings::{self, Action, Mods},
+    gamepad::{Axis, GamepadState},
+};
+
+use glfw::{self, Key};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    mem,
+};
+
+/// Keyboard and mouse state of a frame, given to the scene objects instead
+/// of the window so they can be driven without one
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Input {
+    /// Keys held down, modifier keys included
+    pub keys: BTreeSet<Key>,
+    pub left_button: bool,
+    pub middle_button: bool,
+    /// Cursor movement since the last frame, in screen coordinates with Y
+    /// going up
+    pub mouse_delta: (f32, f32),
+    /// Vertical scroll since the last frame
+    pub scroll: f32,
+    pub gamepad: GamepadState,
+    /// Actions of the gamepad buttons held down in the previous frame
+    previous_actions: BTreeSet<Action>,
+    /// Keys held down in the previous frame
+    previous: BTreeSet<Key>,
+    /// Keys that went down since the last frame, from the key events
+    presses: BTreeSet<Key>,
+    /// Seconds each key has been held down
+    durations: BTreeMap<Key, f32>,
+    /// Last cursor position, `None` until the cursor moves
+    cursor: Option<(f32, f32)>,
+}
+
+impl Input {
+    /// Input with `keys` held down and nothing else
+    pub fn with_keys(keys: &[Key]) -> Input {
+        let mut input = Input::default();
+        input.update_keys(keys, 0.);
+        input
+    }
+
+    /// Reads the keys and mouse buttons held down in `window`, the cursor
+    /// movement, scroll and key presses come from events
+    pub fn poll(&mut self, window: &glfw::Window, delta_time: f32) {
+        let keys: Vec<_> = bindings::KEYS
+            .iter()
+            .chain(MODIFIER_KEYS)
+            .cloned()
+            .filter(|&k| window.get_key(k) == glfw::Action::Press)
+            .collect();
+        self.update_keys(&keys, delta_time);
+        let pressed = |b| window.get_mouse_button(b) == glfw::Action::Press;
+        self.left_button = pressed(glfw::MouseButtonLeft);
+        self.middle_button = pressed(glfw::MouseButtonMiddle);
+    }
+
+    /// Starts a frame `delta_time` seconds after the last one, with `keys`
+    /// held down. Keys pressed since the last frame count as held down in
+    /// this one, even when they were released before it.
+    pub fn update_keys(&mut self, keys: &[Key], delta_time: f32) {
+        let presses = mem::take(&mut self.presses);
+        let mut keys: BTreeSet<Key> = keys.iter().cloned().collect();
+        keys.extend(&presses);
+        self.previous = mem::replace(&mut self.keys, keys);
+        // a key released and pressed again between frames goes down anew
+        for k in &presses {
+            self.previous.remove(k);
+        }
+
+        let (keys, previous) = (&self.keys, &self.previous);
+        self.durations
+            .retain(|k, _| keys.contains(k) && previous.contains(k));
+        for &k in keys {
+            let held = self.durations.entry(k).or_insert(0.);
+            if previous.contains(&k) {
+                *held += delta_time;
+            }
+        }
+    }
+
+    /// Records a key press event, so a tap shorter than a frame isn't lost
+    pub fn press_key(&mut self, key: Key) {
+        self.presses.insert(key);
+    }
+
+    /// Starts a frame with the gamepad in `state`
+    pub fn update_gamepad(&mut self, state: GamepadState) {
+        self.previous_actions = mem::replace(&mut self.gamepad, state).actions;
+    }
+
+    /// Adds the movement from the last cursor position, in screen
+    /// coordinates with Y going down
+    pub fn move_cursor(&mut self, x: f32, y: f32) {
+        if let Some((last_x, last_y)) = self.cursor {
+            let (dx, dy) = self.mouse_delta;
+            self.mouse_delta = (dx + x - last_x, dy + last_y - y);
+        }
+        self.cursor = Some((x, y));
+    }
+
+    /// Ignores the next cursor jump, like the one made when it's captured
+    pub fn reset_cursor(&mut self) {
+        self.cursor = None;
+    }
+
+    /// Forgets the cursor movement and scroll once they were used
+    pub fn end_frame(&mut self) {
+        self.mouse_delta = (0., 0.);
+        self.scroll = 0.;
+    }
+
+    pub fn is_pressed(&self, key: Key) -> bool {
+        self.keys.contains(&key)
+    }
+
+    /// Whether `key` went down in this frame
+    pub fn just_pressed(&self, key: Key) -> bool {
+        self.keys.contains(&key) && !self.previous.contains(&key)
+    }
+
+    /// Whether `key` went up in this frame
+    pub fn just_released(&self, key: Key) -> bool {
+        !self.keys.contains(&key) && self.previous.contains(&key)
+    }
+
+    /// Seconds `key` has been held down, 0 in the frame it's pressed and
+    /// `None` while it's up
+    pub fn held_for(&self, key: Key) -> Option<f32> {
+        self.durations.get(&key).cloned()
+    }
+
+    /// Deflection of a gamepad axis, 0 when it's centered
+    pub fn axis(&self, axis: Axis) -> f32 {
+        self.gamepad.axis(axis)
+    }
+
+    /// Whether a gamepad button of `action` went down in this frame
+    pub fn gamepad_just_pressed(&self, action: Action) -> bool {
+        self.gamepad.actions.contains(&action) && !self.previous_actions.contains(&action)
+    }
+
+    /// Whether the gamepad buttons of `action` went up in this frame
+    pub fn gamepad_just_released(&self, action: Action) -> bool {
+        !self.gamepad.actions.contains(&action) && self.previous_actions.contains(&action)
+    }
+
+    /// Modifiers held down, either the left or the right key
+    pub fn mods(&self) -> Mods {
+        let held = |l, r| self.is_pressed(l) || self.is_pressed(r);
+        Mods {
+            control: held(Key::LeftControl, Key::RightControl),
+            alt: held(Key::LeftAlt, Key::RightAlt),
+            shift: held(Key::LeftShift, Key::RightShift),
+        }
+    }
+}
+
+const MODIFIER_KEYS: &[Key] = &[
+    Key::LeftShift,
+    Key::RightShift,
+    Key::LeftControl,
+    Key::RightControl,
+    Key::LeftAlt,
+    Key::RightAlt,
+];
@@ -3,11 +3,25 @@
 #[macro_use]
 mod macros;
 
+pub(crate) mod bindings;
+pub(crate) mod bookmark;
+pub(crate) mod bounds;
 pub(crate) mod camera;
+pub(crate) mod camera_path;
+pub(crate) mod capture;
+pub(crate) mod gamepad;
+pub(crate) mod input;
 pub(crate) mod mesh;
 pub(crate) mod model;
 pub(crate) mod model_pos;
+pub(crate) mod offscreen;
+pub(crate) mod picking;
+pub(crate) mod projection;
 pub(crate) mod scene;
+pub(crate) mod scene_file;
 pub(crate) mod shader;
+pub(crate) mod time_control;
+pub(crate) mod viewport;
 
-pub use scene::Scene;
+pub use projection::Projection;
+pub use scene::{Scene, Settings};
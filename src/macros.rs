@@ -14,14 +14,9 @@ macro_rules! offset_of {
     };
 }
 
-macro_rules! process_keys {
-    ($window:expr; $( $key:expr, $mode:expr => $code:expr ),+) => {
-        $( if $window.get_key($key) == $mode { $code } )*
-    };
-    ($window:expr; $( $key:expr, $mode:expr => $code:expr, $shift:expr ),+) => {
-        $( if $window.get_key($key) == $mode {
-            if $window.get_key(glfw::Key::LeftShift) == glfw::Action::Press { $shift }
-            else { $code }
-        })*
+/// Runs the code of every action whose keys are held down
+macro_rules! process_actions {
+    ($bindings:expr, $input:expr; $( $action:expr => $code:expr ),+ $(,)*) => {
+        $( if $bindings.is_held($input, $action) { $code } )*
     };
 }
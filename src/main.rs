@@ -1,4 +1,4 @@
-use cg_ufpel_project;
+use cg_ufpel_project::{self, Projection};
 
 use std::path::PathBuf;
 use structopt::StructOpt;
@@ -22,13 +22,145 @@ struct Opt {
         default_value = "configs/model_config.json"
     )]
     config: PathBuf,
+    /// JSON file describing the models, instances and cameras, replaces
+    /// `--models`
+    #[structopt(long = "scene")]
+    scene: Option<PathBuf>,
+    /// Names of the models selected at start, can be repeated
+    #[structopt(long = "select")]
+    select: Vec<String>,
+    /// Mouse look sensitivity, in degrees per pixel
+    #[structopt(long = "sensitivity", default_value = "0.15")]
+    sensitivity: f32,
+    /// Camera fly speed, in units per second
+    #[structopt(long = "camera-speed", default_value = "5")]
+    camera_speed: f32,
+    /// Start with an orthographic projection
+    #[structopt(long = "orthographic")]
+    orthographic: bool,
+    /// Vertical field of view of the perspective projection, in degrees
+    #[structopt(long = "fov", default_value = "45")]
+    fov: f32,
+    /// Vertical size of the orthographic projection, in world units
+    #[structopt(long = "ortho-height", default_value = "10")]
+    ortho_height: f32,
+    #[structopt(long = "near", default_value = "0.1")]
+    near: f32,
+    #[structopt(long = "far", default_value = "100")]
+    far: f32,
+    /// JSON file with a flythrough for the main camera, played with `Y`
+    #[structopt(long = "camera-path")]
+    camera_path: Option<PathBuf>,
+    /// Start in fullscreen, F11 toggles it while running
+    #[structopt(long = "fullscreen")]
+    fullscreen: bool,
+    /// Monitor used in fullscreen, 0 is the primary monitor
+    #[structopt(long = "monitor", default_value = "0")]
+    monitor: usize,
+    /// Don't wait for the vertical sync when swapping buffers
+    #[structopt(long = "no-vsync")]
+    no_vsync: bool,
+    /// Record every frame to a `.y4m` file or a directory of PNG images, F10
+    /// toggles recording to `recording/` otherwise
+    #[structopt(long = "record")]
+    record: Option<PathBuf>,
+    /// Simulated frames per second of recordings
+    #[structopt(long = "record-fps", default_value = "30")]
+    record_fps: f32,
+    /// JSON file mapping action names to keys, see `configs/bindings.json`
+    #[structopt(long = "bindings")]
+    bindings: Option<PathBuf>,
+    /// JSON file mapping gamepad axes and buttons, see
+    /// `configs/gamepad_linux.json`. The default suits XInput on Windows.
+    #[structopt(long = "gamepad")]
+    gamepad: Option<PathBuf>,
+    #[structopt(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, StructOpt)]
+enum Command {
+    /// Renders a single frame to an image with a hidden window, set
+    /// LIBGL_ALWAYS_SOFTWARE=1 to use Mesa's llvmpipe without a GPU
+    #[structopt(name = "render")]
+    Render {
+        /// Image to write, its extension chooses the format
+        #[structopt(short = "o", long = "output", default_value = "render.png")]
+        output: PathBuf,
+        /// Image width, the window width when missing
+        #[structopt(long = "width")]
+        width: Option<u32>,
+        /// Image height, the window height when missing
+        #[structopt(long = "height")]
+        height: Option<u32>,
+        /// Seconds of simulation to run before rendering
+        #[structopt(long = "time", default_value = "0")]
+        time: f32,
+        /// Start the command list animation of every model
+        #[structopt(long = "animate")]
+        animate: bool,
+    },
 }
 
 pub fn run() -> Result<(), failure::Error> {
     let opt = Opt::from_args();
-    let mut scene =
-        cg_ufpel_project::Scene::init(opt.scr_width, opt.scr_height, opt.n_models, opt.config)?;
-    scene.run()
+    let projection = if opt.orthographic {
+        Projection::Orthographic {
+            height: opt.ortho_height,
+            near: opt.near,
+            far: opt.far,
+        }
+    } else {
+        Projection::Perspective {
+            fov: opt.fov,
+            near: opt.near,
+            far: opt.far,
+        }
+    };
+    projection.check()?;
+    let mut scene = cg_ufpel_project::Scene::init(cg_ufpel_project::Settings {
+        wscreen: opt.scr_width,
+        hscreen: opt.scr_height,
+        n_models: opt.n_models,
+        models_config: opt.config,
+        scene: opt.scene,
+        select: opt.select,
+        sensitivity: opt.sensitivity,
+        camera_speed: opt.camera_speed,
+        projection,
+        camera_path: opt.camera_path,
+        fullscreen: opt.fullscreen,
+        monitor: opt.monitor,
+        vsync: !opt.no_vsync,
+        visible: opt.command.is_none(),
+        record: opt.record,
+        record_fps: opt.record_fps,
+        bindings: opt.bindings,
+        gamepad: opt.gamepad,
+    })?;
+
+    match opt.command {
+        None => scene.run(),
+        Some(Command::Render {
+            output,
+            width,
+            height,
+            time,
+            animate,
+        }) => {
+            if animate {
+                scene.start_animations();
+            }
+            scene.advance(time);
+            let (width, height) = (
+                width.unwrap_or(opt.scr_width),
+                height.unwrap_or(opt.scr_height),
+            );
+            scene.render_to_file(&output, width, height)?;
+            println!("Rendered {}x{} to {}", width, height, output.display());
+            Ok(())
+        }
+    }
 }
 
 fn main() {
@@ -1,4 +1,7 @@
-use super::shader::Shader;
+use super::{
+    bounds::{Aabb, Sphere},
+    shader::Shader,
+};
 
 use gl;
 
@@ -49,6 +52,10 @@ pub struct Mesh {
     pub textures: Vec<Texture>,
     pub vao: u32,
 
+    /* Bounding volumes, in model space */
+    pub aabb: Aabb,
+    pub sphere: Sphere,
+
     /* Render data */
     vbo: u32,
     ebo: u32,
@@ -56,11 +63,14 @@ pub struct Mesh {
 
 impl Mesh {
     pub fn new(vertices: Vec<Vertex>, indices: Vec<u32>, textures: Vec<Texture>) -> Mesh {
+        let aabb = Aabb::from_points(vertices.iter().map(|v| v.position));
         let mut mesh = Mesh {
             vertices,
             indices,
             textures,
             vao: 0,
+            aabb,
+            sphere: Sphere::around(&aabb),
             vbo: 0,
             ebo: 0,
         };
@@ -1,11 +1,13 @@
 use super::{
+    bounds::Frustum,
     mesh::{Mesh, Texture, Vertex},
+    picking::Ray,
     shader::Shader,
 };
 
 use gl;
 
-use cgmath::{vec2, vec3};
+use cgmath::{vec2, vec3, Matrix4};
 use image::{self, DynamicImage::*, GenericImage};
 use std::{os::raw::c_void, path::Path};
 use tobj;
@@ -33,6 +35,36 @@ impl Model {
         }
     }
 
+    /// Draws the meshes inside `frustum` once placed by `model`, returning
+    /// how many were drawn and culled
+    pub fn draw_culled(
+        &self,
+        shader: &Shader,
+        model: &Matrix4<f32>,
+        frustum: &Frustum,
+    ) -> (usize, usize) {
+        let (visible, culled): (Vec<_>, Vec<_>) = self.meshes.iter().partition(|mesh| {
+            frustum.intersects_sphere(&mesh.sphere.transformed(model))
+                && frustum.intersects_aabb(&mesh.aabb.transformed(model))
+        });
+        for mesh in &visible {
+            unsafe {
+                mesh.draw(shader);
+            }
+        }
+        (visible.len(), culled.len())
+    }
+
+    /// Distance along `ray` to the nearest mesh once placed by `model`
+    pub fn pick(&self, ray: &Ray, model: &Matrix4<f32>) -> Option<f32> {
+        self.meshes
+            .iter()
+            .filter_map(|m| ray.intersect_mesh(model, &m.aabb, &m.vertices, &m.indices))
+            .fold(None, |nearest: Option<f32>, t| {
+                Some(nearest.map_or(t, |n| n.min(t)))
+            })
+    }
+
     // loads a model from file and stores the resulting meshes in the meshes vector.
     fn load_model(&mut self, path: &str) {
         let path = Path::new(path);
@@ -1,4 +1,4 @@
-use super::Command;
+use super::{Command, Constraints, Pivot, Space, Transform};
 
 use failure::format_err;
 use serde::Deserialize;
@@ -9,6 +9,9 @@ const BASE_SPEED: f32 = 8.;
 const ROTATION_SPEED: f32 = 30.;
 const CIRCLE_SPEED: f32 = 60.;
 const SCALE_SPEED: f32 = 2.;
+const LOOK_SPEED: f32 = 90.;
+const CAMERA_OFFSET: [f32; 3] = [0., 2., 6.];
+const CAMERA_STIFFNESS: f32 = 30.;
 
 #[derive(Clone, Debug, Deserialize, PartialEq)]
 pub struct Configuration {
@@ -16,6 +19,24 @@ pub struct Configuration {
     pub rotation_speed: f32,
     pub circle_speed: f32,
     pub scale_speed: f32,
+    #[serde(default = "look_speed")]
+    pub look_speed: f32,
+
+    /// Position of the follow camera, in the model's local frame
+    #[serde(default = "camera_offset")]
+    pub camera_offset: [f32; 3],
+    /// Spring stiffness used to smooth the follow camera
+    #[serde(default = "camera_stiffness")]
+    pub camera_stiffness: f32,
+
+    #[serde(default)]
+    pub space: Space,
+    #[serde(default)]
+    pub pivot: Pivot,
+    #[serde(default)]
+    pub transform: Option<Transform>,
+    #[serde(default)]
+    pub constraints: Constraints,
 
     pub command_list: Vec<(Command, f32)>,
 }
@@ -27,12 +48,33 @@ impl Default for Configuration {
             rotation_speed: ROTATION_SPEED,
             circle_speed: CIRCLE_SPEED,
             scale_speed: SCALE_SPEED,
+            look_speed: LOOK_SPEED,
+
+            camera_offset: CAMERA_OFFSET,
+            camera_stiffness: CAMERA_STIFFNESS,
+
+            space: Space::default(),
+            pivot: Pivot::default(),
+            transform: None,
+            constraints: Constraints::default(),
 
             command_list: Vec::default(),
         }
     }
 }
 
+fn look_speed() -> f32 {
+    LOOK_SPEED
+}
+
+fn camera_offset() -> [f32; 3] {
+    CAMERA_OFFSET
+}
+
+fn camera_stiffness() -> f32 {
+    CAMERA_STIFFNESS
+}
+
 impl Configuration {
     pub fn from_path<P>(path: P) -> Result<Configuration, failure::Error>
     where
@@ -59,6 +101,15 @@ mod test {
                 rotation_speed: 15.,
                 circle_speed: 30.,
                 scale_speed: 2.,
+                look_speed: LOOK_SPEED,
+
+                camera_offset: CAMERA_OFFSET,
+                camera_stiffness: CAMERA_STIFFNESS,
+
+                space: Space::Local,
+                pivot: Pivot::default(),
+                transform: None,
+                constraints: Constraints::default(),
 
                 command_list: vec!(
                     (Command::ScaleU, 0.5),
@@ -80,5 +131,12 @@ mod test {
             })))
             .unwrap()
         );
+
+        // Only the fields added after the original ones are optional
+        assert!(serde_json::from_value::<Configuration>(json!({
+            "base_speed": 4.0,
+            "command_list": [],
+        }))
+        .is_err());
     }
 }
@@ -0,0 +1,210 @@
+use cgmath::{Deg, InnerSpace, Quaternion, Rad, Rotation3, Vector3};
+use serde::Deserialize;
+
+const MIN_SCALE: f32 = 0.05;
+
+/// Axis aligned region the translation must stay inside
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct Bounds {
+    pub min: [f32; 3],
+    pub max: [f32; 3],
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Constraints {
+    pub bounds: Option<Bounds>,
+    pub lock_translation: [bool; 3],
+    /// Local axes the model can't turn around
+    pub lock_rotation: [bool; 3],
+    pub min_scale: f32,
+    pub max_scale: Option<f32>,
+    /// Per local axis (min, max) twist away from the identity orientation,
+    /// in degrees
+    pub rotation_limits: [Option<(f32, f32)>; 3],
+}
+
+impl Default for Constraints {
+    fn default() -> Self {
+        Constraints {
+            bounds: None,
+            lock_translation: [false; 3],
+            lock_rotation: [false; 3],
+            min_scale: MIN_SCALE,
+            max_scale: None,
+            rotation_limits: [None; 3],
+        }
+    }
+}
+
+impl Constraints {
+    pub fn translation(&self, previous: Vector3<f32>, current: Vector3<f32>) -> Vector3<f32> {
+        let mut t = current;
+        for i in 0..3 {
+            if self.lock_translation[i] {
+                t[i] = previous[i];
+            }
+            if let Some(ref b) = self.bounds {
+                t[i] = t[i].max(b.min[i]).min(b.max[i]);
+            }
+        }
+        t
+    }
+
+    pub fn orientation(
+        &self,
+        previous: Quaternion<f32>,
+        current: Quaternion<f32>,
+    ) -> Quaternion<f32> {
+        let is_free = self.lock_rotation.iter().all(|l| !l)
+            && self.rotation_limits.iter().all(|l| l.is_none());
+        if is_free {
+            return current;
+        }
+
+        // Locks drop the twist of the change in orientation around the
+        // locked axes, keeping only the turn around a single free axis when
+        // two of them are locked
+        let delta = previous.conjugate() * current;
+        let free: Vec<_> = (0..3).filter(|&i| !self.lock_rotation[i]).collect();
+        let delta = match free.len() {
+            0 => Quaternion::from_sv(1., Vector3::new(0., 0., 0.)),
+            1 => swing_twist(delta, axis(free[0])).1,
+            2 => {
+                let locked = (0..3).find(|&i| self.lock_rotation[i]).unwrap();
+                swing_twist(delta, axis(locked)).0
+            }
+            _ => delta,
+        };
+        let mut q = (previous * delta).normalize();
+
+        // Limits clamp the twist around each axis, which unlike Euler angles
+        // doesn't jump when another axis reaches 90 degrees
+        for i in 0..3 {
+            if let Some((min, max)) = self.rotation_limits[i] {
+                let (swing, twist) = swing_twist(q, axis(i));
+                let angle = twist_angle(twist, axis(i));
+                let clamped = angle.max(min).min(max);
+                if clamped != angle {
+                    q = swing * Quaternion::from_axis_angle(axis(i), Deg(clamped));
+                }
+            }
+        }
+        q
+    }
+
+    /// Limits the size along each axis, keeping a mirrored axis mirrored
+    pub fn scale(&self, scale: Vector3<f32>) -> Vector3<f32> {
+        let mut scale = scale;
+        for i in 0..3 {
+            let mut size = scale[i].abs().max(self.min_scale);
+            if let Some(max) = self.max_scale {
+                size = size.min(max);
+            }
+            scale[i] = size.copysign(scale[i]);
+        }
+        scale
+    }
+}
+
+fn axis(i: usize) -> Vector3<f32> {
+    let mut v = Vector3::new(0., 0., 0.);
+    v[i] = 1.;
+    v
+}
+
+/// Splits `q` into a swing, turning `axis` somewhere else, after a twist
+/// around `axis`, so that `q = swing * twist`
+fn swing_twist(q: Quaternion<f32>, axis: Vector3<f32>) -> (Quaternion<f32>, Quaternion<f32>) {
+    let projected = axis * q.v.dot(axis);
+    let twist = Quaternion::from_sv(q.s, projected);
+    let twist = if twist.magnitude2() > f32::EPSILON {
+        twist.normalize()
+    } else {
+        // Half turn around a perpendicular axis, there is no twist
+        Quaternion::from_sv(1., Vector3::new(0., 0., 0.))
+    };
+    (q * twist.conjugate(), twist)
+}
+
+/// Angle of a twist around `axis`, from -180 to 180 degrees
+fn twist_angle(twist: Quaternion<f32>, axis: Vector3<f32>) -> f32 {
+    let angle = Deg::from(Rad(2. * twist.v.dot(axis).atan2(twist.s))).0;
+    if angle > 180. {
+        angle - 360.
+    } else if angle < -180. {
+        angle + 360.
+    } else {
+        angle
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{vec3, Deg, Rotation3};
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn translation() {
+        let c: Constraints = serde_json::from_value(json!({
+            "bounds": { "min": [-1.0, -1.0, -1.0], "max": [1.0, 1.0, 1.0] },
+            "lock_translation": [false, true, false],
+        }))
+        .unwrap();
+        assert_eq!(
+            vec3(1., 0.5, -0.5),
+            c.translation(vec3(0., 0.5, 0.), vec3(3., 2., -0.5))
+        );
+    }
+
+    #[test]
+    fn scale() {
+        let c = Constraints {
+            max_scale: Some(4.),
+            ..Constraints::default()
+        };
+        assert_eq!(vec3(-1., 4., 2.), c.scale(vec3(-1., 10., 2.)));
+        assert_eq!(
+            vec3(-MIN_SCALE, MIN_SCALE, 4.),
+            c.scale(vec3(-0.01, 0., 20.))
+        );
+    }
+
+    #[test]
+    fn rotation_limits() {
+        let c = Constraints {
+            rotation_limits: [None, Some((-45., 45.)), None],
+            ..Constraints::default()
+        };
+        let identity = Quaternion::from_sv(1., vec3(0., 0., 0.));
+        // Tipped past 90 degrees, where the Euler angles flip
+        let tipped = Quaternion::from_angle_x(Deg(100.));
+        let q = c.orientation(identity, tipped * Quaternion::from_angle_y(Deg(80.)));
+        let expected = tipped * Quaternion::from_angle_y(Deg(45.));
+        assert!(q.dot(expected).abs() > 0.9999);
+
+        let q = c.orientation(identity, tipped * Quaternion::from_angle_y(Deg(-30.)));
+        let expected = tipped * Quaternion::from_angle_y(Deg(-30.));
+        assert!(q.dot(expected).abs() > 0.9999);
+    }
+
+    #[test]
+    fn rotation_locks() {
+        let c = Constraints {
+            lock_rotation: [true, false, true],
+            ..Constraints::default()
+        };
+        let previous = Quaternion::from_angle_z(Deg(90.));
+        let turn = Quaternion::from_angle_y(Deg(30.));
+        let q = c.orientation(
+            previous,
+            previous * turn * Quaternion::from_angle_x(Deg(20.)),
+        );
+        assert!(q.dot(previous * turn).abs() > 0.999);
+
+        let q = c.orientation(previous, previous * Quaternion::from_angle_z(Deg(20.)));
+        assert!(q.dot(previous).abs() > 0.9999);
+    }
+}
@@ -1,28 +1,72 @@
-use cgmath::{vec3, Deg, Matrix4, Quaternion, Rotation, Rotation3, Vector3};
-use glfw;
+use cgmath::{vec3, Deg, InnerSpace, Matrix4, Quaternion, Rad, Rotation, Rotation3, Vector3};
 use std::path::Path;
 
 mod animation;
 mod config;
+mod constraint;
 mod curve;
+mod pivot;
+mod space;
+mod transform;
 
-use crate::scene::SceneObject;
+use crate::{
+    bindings::{Action, Bindings},
+    gamepad::Axis,
+    input::Input,
+};
 use animation::Animation;
 pub use config::Configuration;
+pub use constraint::Constraints;
 use curve::CurveControl;
+pub use pivot::Pivot;
 use serde::Deserialize;
+pub use space::Space;
+pub use transform::Transform;
+
+const WORLD_UP: Vector3<f32> = Vector3 {
+    x: 0.,
+    y: 1.,
+    z: 0.,
+};
 
 #[derive(Clone, Debug)]
 pub struct ModelPosition {
     pub orientation: Quaternion<f32>,
     pub translation: Vector3<f32>,
-    pub scale: f32,
+    pub scale: Vector3<f32>,
 
     pub is_selected: bool,
     pub config: Configuration,
+    pub space: Space,
+    pub view_orientation: Quaternion<f32>,
+    pub pivot: Pivot,
+    /// Set while part of a multiple selection, so the selected models
+    /// rotate and scale as one
+    pub group: Option<Group>,
     curve: CurveControl,
     animation: Animation,
-    debug_pressed: bool,
+}
+
+/// Multiple selection transformed as one
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Group {
+    /// Point the group rotates and scales around
+    pub centroid: Vector3<f32>,
+    /// Orientation of the first selected model, whose local axes the whole
+    /// group uses
+    pub orientation: Quaternion<f32>,
+    /// Scale of the first selected model, which sets the factor the whole
+    /// group scales by
+    pub scale: Vector3<f32>,
+}
+
+/// The rest of the scene, used to resolve pivots and look targets
+#[derive(Copy, Clone, Debug)]
+pub struct Surroundings<'a> {
+    /// Translation of every model, indexed like `Pivot::Model`
+    pub translations: &'a [Vector3<f32>],
+    /// Mean translation of the selected models
+    pub selection_centroid: Vector3<f32>,
 }
 
 #[derive(Clone, Debug, PartialEq)]
@@ -35,10 +79,30 @@ pub enum Movement {
     BackwardZ,
 }
 
+impl Movement {
+    /// Signed unit vector of the movement axis
+    pub fn axis(&self) -> Vector3<f32> {
+        match self {
+            Movement::ForwardX => Vector3::unit_x(),
+            Movement::BackwardX => -Vector3::unit_x(),
+            Movement::ForwardY => Vector3::unit_y(),
+            Movement::BackwardY => -Vector3::unit_y(),
+            Movement::ForwardZ => Vector3::unit_z(),
+            Movement::BackwardZ => -Vector3::unit_z(),
+        }
+    }
+}
+
 #[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
 pub enum Command {
     ScaleU,
     ScaleD,
+    ScaleXU,
+    ScaleXD,
+    ScaleYU,
+    ScaleYD,
+    ScaleZU,
+    ScaleZD,
 
     SlideXF,
     SlideXB,
@@ -60,6 +124,21 @@ pub enum Command {
     RotateYB,
     RotateZF,
     RotateZB,
+
+    SetSpace(Space),
+
+    LookAt([f32; 3]),
+    LookAtModel(usize),
+    RotateTo([f32; 3]),
+
+    OrbitXF,
+    OrbitXB,
+    OrbitYF,
+    OrbitYB,
+    OrbitZF,
+    OrbitZB,
+
+    SetPivot(Pivot),
 }
 
 impl Default for ModelPosition {
@@ -67,12 +146,15 @@ impl Default for ModelPosition {
         ModelPosition {
             orientation: Quaternion::from_sv(1., vec3(0., 0., 0.)),
             translation: vec3(0., 0., 0.),
-            scale: 1.,
+            scale: vec3(1., 1., 1.),
 
             is_selected: false,
             config: Configuration::default(),
+            space: Space::default(),
+            view_orientation: Quaternion::from_sv(1., vec3(0., 0., 0.)),
+            pivot: Pivot::default(),
+            group: None,
 
-            debug_pressed: false,
             curve: CurveControl::default(),
             animation: Animation::default(),
         }
@@ -86,48 +168,153 @@ impl ModelPosition {
     {
         let mut m = ModelPosition::default();
         m.config = Configuration::from_path(path)?;
+        m.space = m.config.space;
+        m.pivot = m.config.pivot;
+        match m.config.transform.clone() {
+            Some(t) => m.set_transform(&t),
+            None => m.clamp(),
+        }
         Ok(m)
     }
 
     pub fn matrix(&self) -> Matrix4<f32> {
-        let tmat = Matrix4::from_translation(self.translation);
-        let omat = Matrix4::from(self.orientation);
-        let smat = Matrix4::from_scale(self.scale);
-        tmat * omat * smat
+        transform::compose(self.translation, self.orientation, self.scale)
+    }
+
+    /// Replaces translation, orientation and scale with the ones of `m`
+    pub fn set_matrix(&mut self, m: Matrix4<f32>) {
+        let (translation, orientation, scale) = transform::decompose(m);
+        self.translation = translation;
+        self.orientation = orientation;
+        self.scale = scale;
+        self.clamp();
+    }
+
+    pub fn transform(&self) -> Transform {
+        let (t, s) = (self.translation, self.scale);
+        Transform {
+            translation: [t.x, t.y, t.z],
+            rotation: self.euler_degrees(),
+            scale: [s.x, s.y, s.z],
+        }
+    }
+
+    pub fn set_transform(&mut self, t: &Transform) {
+        self.translation = t.translation.into();
+        self.set_euler_degrees(t.rotation);
+        self.scale = t.scale.into();
+        self.clamp();
+    }
+
+    /// Orientation as X, Y, Z Euler angles in degrees
+    pub fn euler_degrees(&self) -> [f32; 3] {
+        transform::to_euler_degrees(self.orientation)
+    }
+
+    pub fn set_euler_degrees(&mut self, angles: [f32; 3]) {
+        self.orientation = transform::from_euler_degrees(angles);
     }
 
     pub fn scale_up(&mut self, delta_time: f32) {
-        self.scale += self.config.scale_speed * delta_time;
+        self.scale_in(self.space, self.config.scale_speed * delta_time);
     }
 
     pub fn scale_down(&mut self, delta_time: f32) {
-        self.scale -= self.config.scale_speed * delta_time;
+        self.scale_in(self.space, -self.config.scale_speed * delta_time);
+    }
+
+    /// Uniform scale looks the same in every space, but only the local one
+    /// keeps a grouped model in place, the others spread the group from its
+    /// centroid as much as the model could grow
+    pub fn scale_in(&mut self, space: Space, step: f32) {
+        let before = self.scale.x;
+        match self.group {
+            Some(g) => self.scale *= scale_factor(g.scale.x, step),
+            // a mirrored axis grows away from zero too
+            None => self.scale += self.scale.map(f32::signum) * step,
+        }
+        self.scale = self.config.constraints.scale(self.scale);
+        if let Some(g) = self.group {
+            if space != Space::Local && before != 0. {
+                let c = g.centroid;
+                self.translation = c + (self.translation - c) * (self.scale.x / before);
+            }
+        }
+    }
+
+    /// Scales along a single axis, forward directions grow and backward ones
+    /// shrink. Scale is always stored in local space, so for other spaces the
+    /// local axis closest to the requested one is used
+    pub fn scale_axis(&mut self, direction: Movement, delta_time: f32) {
+        let world = self.world_axis(self.space, &direction);
+        let i = nearest_axis(self.orientation, world);
+        let grow = match direction {
+            Movement::ForwardX | Movement::ForwardY | Movement::ForwardZ => 1.,
+            _ => -1.,
+        };
+        let step = grow * self.config.scale_speed * delta_time;
+        let before = self.scale[i];
+        match self.group {
+            Some(g) => {
+                let lead = g.scale[nearest_axis(g.orientation, world)];
+                self.scale[i] *= scale_factor(lead, step);
+            }
+            None => self.scale[i] += step * self.scale[i].signum(),
+        }
+        self.scale = self.config.constraints.scale(self.scale);
+        if let Some(g) = self.group {
+            if self.space != Space::Local && before != 0. {
+                // spread the group along the same axis
+                let along = world * world.dot(self.translation - g.centroid);
+                self.translation += along * (self.scale[i] / before - 1.);
+            }
+        }
+    }
+
+    /// World direction of `direction` in `space`, a group uses the local
+    /// axes of its first model so it moves as one
+    fn world_axis(&self, space: Space, direction: &Movement) -> Vector3<f32> {
+        let orientation = match self.group {
+            Some(g) => g.orientation,
+            None => self.orientation,
+        };
+        space.to_world(direction.axis(), orientation, self.view_orientation)
     }
 
     pub fn slide(&mut self, direction: Movement, delta_time: f32) {
+        self.slide_in(self.space, direction, delta_time);
+    }
+
+    pub fn slide_in(&mut self, space: Space, direction: Movement, delta_time: f32) {
         let step = self.config.base_speed * delta_time;
-        match direction {
-            Movement::ForwardX => self.translation.x += step,
-            Movement::BackwardX => self.translation.x -= step,
-            Movement::ForwardY => self.translation.y += step,
-            Movement::BackwardY => self.translation.y -= step,
-            Movement::ForwardZ => self.translation.z += step,
-            Movement::BackwardZ => self.translation.z -= step,
-        }
+        let axis = self.world_axis(space, &direction);
+        self.translation += axis * step;
         self.curve.reset();
     }
 
     pub fn rotate(&mut self, direction: Movement, delta_time: f32) {
+        self.rotate_in(self.space, direction, delta_time);
+    }
+
+    pub fn rotate_in(&mut self, space: Space, direction: Movement, delta_time: f32) {
+        // Rotating around the world version of a local axis is the same as
+        // post-multiplying by the local rotation, so every space can be
+        // handled as a world rotation
         let step = Deg(self.config.rotation_speed * delta_time);
-        let rot = match direction {
-            Movement::ForwardX => Quaternion::from_angle_x(step),
-            Movement::BackwardX => Quaternion::from_angle_x(-step),
-            Movement::ForwardY => Quaternion::from_angle_y(step),
-            Movement::BackwardY => Quaternion::from_angle_y(-step),
-            Movement::ForwardZ => Quaternion::from_angle_z(step),
-            Movement::BackwardZ => Quaternion::from_angle_z(-step),
-        };
-        self.orientation = self.orientation * rot;
+        let axis = self.world_axis(space, &direction);
+        let rot = Quaternion::from_axis_angle(axis, step);
+        let previous = self.orientation;
+        self.orientation = self
+            .config
+            .constraints
+            .orientation(previous, rot * previous);
+        if let Some(g) = self.group {
+            // orbit only as much as the constraints let the model turn
+            let turned = self.orientation * previous.invert();
+            let c = g.centroid;
+            self.translation = turned * (self.translation - c) + c;
+            self.curve.reset();
+        }
     }
 
     pub fn rotate_around(&mut self, direction: Movement, p: Vector3<f32>, delta_time: f32) {
@@ -144,30 +331,157 @@ impl ModelPosition {
         self.curve.reset();
     }
 
-    pub fn look_at(&mut self, p: Vector3<f32>, up: Vector3<f32>, delta_time: f32) {
+    /// World position of the current pivot
+    pub fn pivot_point(&self, scene: &Surroundings) -> Vector3<f32> {
+        match self.pivot {
+            Pivot::Point(p) => p.into(),
+            Pivot::Model(i) => scene
+                .translations
+                .get(i)
+                .cloned()
+                .unwrap_or_else(|| vec3(0., 0., 0.)),
+            Pivot::Centroid => scene.selection_centroid,
+        }
+    }
+
+    pub fn orbit(&mut self, direction: Movement, scene: &Surroundings, delta_time: f32) {
+        let p = self.pivot_point(scene);
+        self.rotate_around(direction, p, delta_time);
+    }
+
+    fn facing(dir: Vector3<f32>, up: Vector3<f32>) -> Quaternion<f32> {
         // Quaternion::look_at currenty used rh system;
         // https://github.com/rustgd/cgmath/issues/448
         // so we need to correct the orientation with two steps
         //   (1) invert the look_at direction;
         //   (2) invert the resulting quaternion vector;
-        let dir = p - self.translation;
+        // Looking straight along `up` leaves the roll undefined, so another
+        // up is used there
+        let up = if dir.cross(up).magnitude2() <= 1e-6 * dir.magnitude2() * up.magnitude2() {
+            Vector3::unit_z()
+        } else {
+            up
+        };
         let rot = Quaternion::look_at(-dir, up);
-        let rot = Quaternion::from_sv(rot.s, -rot.v);
+        Quaternion::from_sv(rot.s, -rot.v)
+    }
 
+    pub fn look_at(&mut self, p: Vector3<f32>, up: Vector3<f32>, delta_time: f32) {
         // delta_time is not currently used because we want the look at to be abrupt
         let _ = delta_time;
 
-        self.orientation = rot;
+        let dir = p - self.translation;
+        if dir.magnitude2() > 0. {
+            self.orientation = Self::facing(dir, up);
+        }
+    }
+
+    /// Smooth version of `look_at`, limited by `config.look_speed`
+    pub fn look_towards(&mut self, p: Vector3<f32>, up: Vector3<f32>, delta_time: f32) {
+        let dir = p - self.translation;
+        self.rotate_towards(dir, up, delta_time);
+    }
+
+    /// Slerps the orientation towards facing `dir`, turning at most
+    /// `config.look_speed` degrees per second
+    pub fn rotate_towards(&mut self, dir: Vector3<f32>, up: Vector3<f32>, delta_time: f32) {
+        if dir.magnitude2() == 0. {
+            return;
+        }
+
+        let current = self.orientation.normalize();
+        let mut target = Self::facing(dir, up);
+        // q and -q are the same rotation, take the shortest path
+        if current.dot(target) < 0. {
+            target = -target;
+        }
+
+        let angle = Deg::from(Rad(current.dot(target).min(1.).acos() * 2.));
+        let step = Deg(self.config.look_speed * delta_time);
+        self.orientation = if angle <= step {
+            target
+        } else {
+            current.slerp(target, step / angle)
+        };
     }
 
     pub fn slide_curve(&mut self, direction: Movement, delta_time: f32) {
         self.translation = self.curve.slide(self.translation, direction, delta_time);
     }
 
-    pub fn run_command(&mut self, c: Command, delta_time: f32) {
+    /// Enforces `config.constraints`, reverting locked axes to the given
+    /// previous state
+    pub fn constrain(&mut self, translation: Vector3<f32>, orientation: Quaternion<f32>) {
+        let constraints = &self.config.constraints;
+        self.translation = constraints.translation(translation, self.translation);
+        self.orientation = constraints.orientation(orientation, self.orientation);
+        self.scale = constraints.scale(self.scale);
+    }
+
+    /// Moves the current state inside the bounds and limits of
+    /// `config.constraints`, used after placing the model directly
+    pub fn clamp(&mut self) {
+        let (translation, orientation) = (self.translation, self.orientation);
+        self.constrain(translation, orientation);
+    }
+
+    /// Plays the command list of the configuration
+    pub fn start_animation(&mut self) {
+        self.animation.start(self.config.command_list.clone());
+    }
+
+    pub fn is_animating(&self) -> bool {
+        self.animation.is_running
+    }
+
+    /// Renumbers the models used by the pivot and the commands once some of
+    /// `translations` were removed, `kept` telling which ones remain. A
+    /// removed model is replaced by the point where it was.
+    pub fn remove_models(&mut self, kept: &[bool], translations: &[Vector3<f32>]) {
+        let remap = |i: usize| {
+            if kept.get(i) == Some(&true) {
+                Ok(kept[..i].iter().filter(|&&k| k).count())
+            } else {
+                let p = translations
+                    .get(i)
+                    .cloned()
+                    .unwrap_or_else(|| vec3(0., 0., 0.));
+                Err([p.x, p.y, p.z])
+            }
+        };
+        let pivot = |pivot| match pivot {
+            Pivot::Model(i) => remap(i).map_or_else(Pivot::Point, Pivot::Model),
+            pivot => pivot,
+        };
+        self.pivot = pivot(self.pivot);
+        self.config.pivot = pivot(self.config.pivot);
+        let commands = self
+            .config
+            .command_list
+            .iter_mut()
+            .chain(self.animation.command_pool.iter_mut());
+        for (c, _) in commands {
+            *c = match *c {
+                Command::LookAtModel(i) => {
+                    remap(i).map_or_else(Command::LookAt, Command::LookAtModel)
+                }
+                Command::SetPivot(p) => Command::SetPivot(pivot(p)),
+                c => c,
+            };
+        }
+    }
+
+    pub fn run_command(&mut self, c: Command, scene: &Surroundings, delta_time: f32) {
+        let (translation, orientation) = (self.translation, self.orientation);
         match c {
             Command::ScaleU => self.scale_up(delta_time),
             Command::ScaleD => self.scale_down(delta_time),
+            Command::ScaleXU => self.scale_axis(Movement::ForwardX, delta_time),
+            Command::ScaleXD => self.scale_axis(Movement::BackwardX, delta_time),
+            Command::ScaleYU => self.scale_axis(Movement::ForwardY, delta_time),
+            Command::ScaleYD => self.scale_axis(Movement::BackwardY, delta_time),
+            Command::ScaleZU => self.scale_axis(Movement::ForwardZ, delta_time),
+            Command::ScaleZD => self.scale_axis(Movement::BackwardZ, delta_time),
 
             Command::SlideXF => self.slide(Movement::ForwardX, delta_time),
             Command::SlideXB => self.slide(Movement::BackwardX, delta_time),
@@ -189,15 +503,41 @@ impl ModelPosition {
             Command::RotateYB => self.rotate(Movement::BackwardY, delta_time),
             Command::RotateZF => self.rotate(Movement::ForwardZ, delta_time),
             Command::RotateZB => self.rotate(Movement::BackwardZ, delta_time),
+
+            Command::SetSpace(space) => self.space = space,
+
+            Command::LookAt(p) => self.look_towards(p.into(), WORLD_UP, delta_time),
+            Command::LookAtModel(i) => {
+                if let Some(&p) = scene.translations.get(i) {
+                    self.look_towards(p, WORLD_UP, delta_time);
+                }
+            }
+            Command::RotateTo(dir) => self.rotate_towards(dir.into(), WORLD_UP, delta_time),
+
+            Command::OrbitXF => self.orbit(Movement::ForwardX, scene, delta_time),
+            Command::OrbitXB => self.orbit(Movement::BackwardX, scene, delta_time),
+            Command::OrbitYF => self.orbit(Movement::ForwardY, scene, delta_time),
+            Command::OrbitYB => self.orbit(Movement::BackwardY, scene, delta_time),
+            Command::OrbitZF => self.orbit(Movement::ForwardZ, scene, delta_time),
+            Command::OrbitZB => self.orbit(Movement::BackwardZ, scene, delta_time),
+
+            Command::SetPivot(pivot) => self.pivot = pivot,
         };
+        self.constrain(translation, orientation);
     }
-}
 
-impl SceneObject for ModelPosition {
-    fn process_input(&mut self, window: &glfw::Window, delta_time: f32) {
+    /// Like `SceneObject::process_input`, with the rest of the scene needed
+    /// by pivots and look targets
+    pub fn process_input(
+        &mut self,
+        input: &Input,
+        bindings: &Bindings,
+        scene: &Surroundings,
+        delta_time: f32,
+    ) {
         if self.animation.is_running {
             for (c, t) in self.animation.step(delta_time) {
-                self.run_command(c, t);
+                self.run_command(c, scene, t);
             }
             return;
         }
@@ -206,56 +546,253 @@ impl SceneObject for ModelPosition {
             return;
         }
 
-        process_keys!(
-        window;
-        glfw::Key::W, glfw::Action::Press =>
-                self.slide(Movement::ForwardZ, delta_time),
-                self.slide_curve(Movement::ForwardZ, delta_time),
-        glfw::Key::A, glfw::Action::Press =>
-                self.slide(Movement::BackwardX, delta_time),
-                self.slide_curve(Movement::BackwardX, delta_time),
-        glfw::Key::S, glfw::Action::Press =>
-                self.slide(Movement::BackwardZ, delta_time),
-                self.slide_curve(Movement::BackwardZ, delta_time),
-        glfw::Key::D, glfw::Action::Press =>
-                self.slide(Movement::ForwardX, delta_time),
-                self.slide_curve(Movement::ForwardX, delta_time),
-        glfw::Key::Q, glfw::Action::Press =>
-                self.slide(Movement::ForwardY, delta_time),
-                self.slide_curve(Movement::ForwardY, delta_time),
-        glfw::Key::E, glfw::Action::Press =>
-                self.slide(Movement::BackwardY, delta_time),
-                self.slide_curve(Movement::BackwardY, delta_time),
-        glfw::Key::R, glfw::Action::Press =>
-                self.scale_up(delta_time),
-                self.scale_down(delta_time),
-        glfw::Key::G, glfw::Action::Press =>
-                self.look_at(vec3(0., 0., 0.), vec3(0., 1., 0.), delta_time),
-                self.look_at(vec3(0., 2., 0.), vec3(0., 1., 0.), delta_time),
-        glfw::Key::V, glfw::Action::Press =>
-                self.rotate_around(Movement::ForwardY, vec3(0., 0., 0.), delta_time),
-                self.rotate_around(Movement::BackwardY, vec3(0., 0., 0.), delta_time),
-        glfw::Key::Z, glfw::Action::Press =>
-                self.rotate(Movement::ForwardZ, delta_time),
-                self.rotate(Movement::BackwardZ, delta_time),
-        glfw::Key::X, glfw::Action::Press =>
-                self.rotate(Movement::ForwardX, delta_time),
-                self.rotate(Movement::BackwardX, delta_time),
-        glfw::Key::C, glfw::Action::Press =>
-                self.rotate(Movement::ForwardY, delta_time),
-                self.rotate(Movement::BackwardY, delta_time)
+        let (translation, orientation) = (self.translation, self.orientation);
+
+        process_actions!(
+        bindings, input;
+        Action::SlideForwardX => self.slide(Movement::ForwardX, delta_time),
+        Action::SlideBackwardX => self.slide(Movement::BackwardX, delta_time),
+        Action::SlideForwardY => self.slide(Movement::ForwardY, delta_time),
+        Action::SlideBackwardY => self.slide(Movement::BackwardY, delta_time),
+        Action::SlideForwardZ => self.slide(Movement::ForwardZ, delta_time),
+        Action::SlideBackwardZ => self.slide(Movement::BackwardZ, delta_time),
+        Action::CurveForwardX => self.slide_curve(Movement::ForwardX, delta_time),
+        Action::CurveBackwardX => self.slide_curve(Movement::BackwardX, delta_time),
+        Action::CurveForwardY => self.slide_curve(Movement::ForwardY, delta_time),
+        Action::CurveBackwardY => self.slide_curve(Movement::BackwardY, delta_time),
+        Action::CurveForwardZ => self.slide_curve(Movement::ForwardZ, delta_time),
+        Action::CurveBackwardZ => self.slide_curve(Movement::BackwardZ, delta_time),
+        Action::ScaleUp => self.scale_up(delta_time),
+        Action::ScaleDown => self.scale_down(delta_time),
+        Action::FaceOrigin => self.look_towards(vec3(0., 0., 0.), WORLD_UP, delta_time),
+        Action::FaceAboveOrigin => self.look_towards(vec3(0., 2., 0.), WORLD_UP, delta_time),
+        Action::OrbitForward => self.orbit(Movement::ForwardY, scene, delta_time),
+        Action::OrbitBackward => self.orbit(Movement::BackwardY, scene, delta_time),
+        Action::RotateForwardX => self.rotate(Movement::ForwardX, delta_time),
+        Action::RotateBackwardX => self.rotate(Movement::BackwardX, delta_time),
+        Action::RotateForwardY => self.rotate(Movement::ForwardY, delta_time),
+        Action::RotateBackwardY => self.rotate(Movement::BackwardY, delta_time),
+        Action::RotateForwardZ => self.rotate(Movement::ForwardZ, delta_time),
+        Action::RotateBackwardZ => self.rotate(Movement::BackwardZ, delta_time),
+        Action::StartAnimation => self.start_animation(),
         );
 
-        process_keys!(
-        window;
-        glfw::Key::H, glfw::Action::Press => self.animation.start(self.config.command_list.clone()),
-        glfw::Key::F, glfw::Action::Release => self.debug_pressed = false,
-        glfw::Key::F, glfw::Action::Press => {
-            if self.debug_pressed == false {
-                self.debug_pressed = true;
-                println!("Model_pos: {:#?}", self);
-                println!("Delta time: {:#?}", delta_time);
-            }
+        // Analog sticks and triggers, faster the further they're pushed
+        let (x, y, z) = (
+            input.axis(Axis::ModelX),
+            input.axis(Axis::ModelY),
+            input.axis(Axis::ModelZ),
+        );
+        if x != 0. || y != 0. || z != 0. {
+            self.slide(Movement::ForwardX, x * delta_time);
+            self.slide(Movement::ForwardY, y * delta_time);
+            self.slide(Movement::ForwardZ, z * delta_time);
+        }
+        self.scale_up(input.axis(Axis::ScaleUp) * delta_time);
+        self.scale_down(input.axis(Axis::ScaleDown) * delta_time);
+
+        if bindings.just_pressed(input, Action::CycleSpace) {
+            self.space = self.space.next();
+            println!("Transform space: {:?}", self.space);
+        }
+
+        if bindings.just_pressed(input, Action::CyclePivot) {
+            self.pivot = self.pivot.next(scene.translations.len());
+            println!("Pivot: {:?}", self.pivot);
+        }
+        if bindings.just_pressed(input, Action::PinPivot) {
+            // Pin the pivot where it currently is
+            let p = self.pivot_point(scene);
+            self.pivot = Pivot::Point([p.x, p.y, p.z]);
+            println!("Pivot: {:?}", self.pivot);
+        }
+
+        if bindings.just_pressed(input, Action::DebugModel) {
+            println!("Model_pos: {:#?}", self);
+            println!("Euler angles: {:?}", self.euler_degrees());
+            println!("Delta time: {:#?}", delta_time);
+        }
+
+        self.constrain(translation, orientation);
+    }
+}
+
+/// Index of the local axis of `orientation` closest to the world `axis`
+fn nearest_axis(orientation: Quaternion<f32>, axis: Vector3<f32>) -> usize {
+    let local = orientation.invert().rotate_vector(axis);
+    (0..3)
+        .max_by(|&a, &b| local[a].abs().partial_cmp(&local[b].abs()).unwrap())
+        .unwrap_or(0)
+}
+
+/// Factor growing `size` by `step` scales it by, never flipping it
+fn scale_factor(size: f32, step: f32) -> f32 {
+    let size = size.abs();
+    if size > f32::EPSILON {
+        ((size + step) / size).max(f32::EPSILON)
+    } else {
+        1.
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    const SCENE: Surroundings = Surroundings {
+        translations: &[],
+        selection_centroid: Vector3 {
+            x: 0.,
+            y: 0.,
+            z: 0.,
+        },
+    };
+
+    #[test]
+    fn look_commands() {
+        assert_eq!(
+            vec![
+                (Command::LookAt([0., 1., 0.]), 1.),
+                (Command::LookAtModel(2), 0.5),
+                (Command::RotateTo([1., 0., 0.]), 0.5),
+            ],
+            serde_json::from_value::<Vec<(Command, f32)>>(json!([
+                [{ "LookAt": [0.0, 1.0, 0.0] }, 1.0],
+                [{ "LookAtModel": 2 }, 0.5],
+                [{ "RotateTo": [1.0, 0.0, 0.0] }, 0.5],
+            ]))
+            .unwrap()
+        );
+    }
+
+    #[test]
+    fn keys_move_selected_models() {
+        let bindings = Bindings::default();
+        let forward = Input::with_keys(&[glfw::Key::W]);
+        let mut m = ModelPosition::default();
+        m.process_input(&forward, &bindings, &SCENE, 1.);
+        assert_eq!(vec3(0., 0., 0.), m.translation);
+
+        m.is_selected = true;
+        m.process_input(&forward, &bindings, &SCENE, 1.);
+        assert_eq!(vec3(0., 0., m.config.base_speed), m.translation);
+
+        // W isn't bound with Ctrl, so it still moves
+        let ctrl = Input::with_keys(&[glfw::Key::LeftControl, glfw::Key::W]);
+        m.process_input(&ctrl, &bindings, &SCENE, 1.);
+        assert_eq!(vec3(0., 0., 2. * m.config.base_speed), m.translation);
+
+        // holding the toggle key changes the space once
+        let mut input = Input::default();
+        for keys in &[&[glfw::Key::N][..], &[glfw::Key::N], &[], &[glfw::Key::N]] {
+            input.update_keys(keys, 1.);
+            m.process_input(&input, &bindings, &SCENE, 1.);
+        }
+        assert_eq!(Space::View, m.space);
+    }
+
+    #[test]
+    fn look_straight_up_or_down() {
+        let mut m = ModelPosition::default();
+        // Models face their -Z axis towards the target
+        m.run_command(Command::LookAt([0., 2., 0.]), &SCENE, 10.);
+        let forward = m.orientation.rotate_vector(-Vector3::unit_z());
+        assert!((forward - vec3(0., 1., 0.)).magnitude() < 1e-4);
+
+        m.look_at(vec3(0., -1., 0.), WORLD_UP, 0.);
+        let forward = m.orientation.rotate_vector(-Vector3::unit_z());
+        assert!((forward - vec3(0., -1., 0.)).magnitude() < 1e-4);
+        assert!((m.orientation.magnitude() - 1.).abs() < 1e-4);
+    }
+
+    #[test]
+    fn placing_respects_constraints() {
+        let mut m = ModelPosition::default();
+        m.config.constraints = serde_json::from_value(json!({
+            "bounds": { "min": [-1.0, 0.0, -1.0], "max": [1.0, 1.0, 1.0] },
+            "max_scale": 2.0,
+        }))
+        .unwrap();
+        m.set_transform(&Transform {
+            translation: [3., -1., 0.5],
+            rotation: [0., 0., 0.],
+            scale: [1., 5., 1.],
         });
+        assert_eq!(vec3(1., 0., 0.5), m.translation);
+        assert_eq!(vec3(1., 2., 1.), m.scale);
+    }
+
+    #[test]
+    fn rotate_towards_is_rate_limited() {
+        let mut m = ModelPosition::default();
+        let target = ModelPosition::facing(vec3(1., 0., 0.), WORLD_UP);
+
+        // 90 degrees away at 90 degrees per second
+        m.rotate_towards(vec3(1., 0., 0.), WORLD_UP, 0.5);
+        assert!(m.orientation.dot(target).abs() < 0.99);
+
+        m.rotate_towards(vec3(1., 0., 0.), WORLD_UP, 0.5);
+        assert!(m.orientation.dot(target).abs() > 0.9999);
+    }
+
+    #[test]
+    fn rotations_default_to_local_axes() {
+        let mut m = ModelPosition::default();
+        m.config.rotation_speed = 90.;
+        m.rotate(Movement::ForwardX, 1.);
+        m.rotate(Movement::ForwardY, 1.);
+        let expected = Quaternion::from_angle_x(Deg(90.)) * Quaternion::from_angle_y(Deg(90.));
+        assert!(m.orientation.dot(expected).abs() > 0.9999);
+    }
+
+    #[test]
+    fn group_turns_around_centroid() {
+        let mut m = ModelPosition::default();
+        m.config.rotation_speed = 90.;
+        m.config.scale_speed = 1.;
+        m.translation = vec3(2., 0., 0.);
+        m.group = Some(Group {
+            centroid: vec3(1., 0., 0.),
+            orientation: Quaternion::from_sv(1., vec3(0., 0., 0.)),
+            scale: vec3(1., 1., 1.),
+        });
+        m.space = Space::World;
+        let mut other = m.clone();
+        let mut limited = m.clone();
+
+        m.rotate_in(Space::World, Movement::ForwardY, 1.);
+        assert!((m.translation - vec3(1., 0., -1.)).magnitude() < 1e-5);
+
+        m.scale_up(1.);
+        assert!((m.translation - vec3(1., 0., -2.)).magnitude() < 1e-5);
+
+        // the model's X axis now lies along world Z, and the first model
+        // grows by half
+        m.group.as_mut().unwrap().scale = vec3(2., 2., 2.);
+        m.scale_axis(Movement::ForwardZ, 1.);
+        assert!((m.scale - vec3(3., 2., 2.)).magnitude() < 1e-5);
+        assert!((m.translation - vec3(1., 0., -3.)).magnitude() < 1e-5);
+
+        // members of another size scale by the same factor
+        other.scale = vec3(4., 4., 4.);
+        other.scale_up(1.);
+        assert!((other.scale - vec3(8., 8., 8.)).magnitude() < 1e-5);
+        assert!((other.translation - vec3(3., 0., 0.)).magnitude() < 1e-5);
+
+        // local rotations use the axes of the first selected model
+        other.translation = vec3(2., 0., 0.);
+        other.orientation = Quaternion::from_angle_z(Deg(90.));
+        other.rotate_in(Space::Local, Movement::ForwardY, 1.);
+        assert!((other.translation - vec3(1., 0., -1.)).magnitude() < 1e-5);
+
+        // a model held by its constraints stays in place
+        limited.config.constraints.max_scale = Some(1.);
+        limited.config.constraints.lock_rotation = [true, true, true];
+        limited.scale_up(1.);
+        limited.rotate_in(Space::World, Movement::ForwardY, 1.);
+        assert_eq!(vec3(1., 1., 1.), limited.scale);
+        assert!((limited.translation - vec3(2., 0., 0.)).magnitude() < 1e-5);
     }
 }
@@ -0,0 +1,27 @@
+use serde::Deserialize;
+
+/// Point used as the center of `rotate_around` orbits
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub enum Pivot {
+    Point([f32; 3]),
+    Model(usize),
+    Centroid,
+}
+
+impl Default for Pivot {
+    fn default() -> Self {
+        Pivot::Point([0., 0., 0.])
+    }
+}
+
+impl Pivot {
+    /// Next pivot in the cycle used by the runtime toggle key
+    pub fn next(self, n_models: usize) -> Self {
+        match self {
+            Pivot::Point(_) => Pivot::Centroid,
+            Pivot::Centroid if n_models > 0 => Pivot::Model(0),
+            Pivot::Model(i) if i + 1 < n_models => Pivot::Model(i + 1),
+            _ => Pivot::default(),
+        }
+    }
+}
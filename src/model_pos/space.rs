@@ -0,0 +1,73 @@
+use cgmath::{InnerSpace, Quaternion, Rotation, Vector3};
+use serde::Deserialize;
+
+/// Reference frame used to interpret the axes of a transform command
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub enum Space {
+    World,
+    Local,
+    Parent,
+    View,
+}
+
+impl Default for Space {
+    fn default() -> Self {
+        Space::Local
+    }
+}
+
+impl Space {
+    /// Next space in the cycle used by the runtime toggle key
+    pub fn next(self) -> Self {
+        match self {
+            Space::World => Space::Local,
+            Space::Local => Space::Parent,
+            Space::Parent => Space::View,
+            Space::View => Space::World,
+        }
+    }
+
+    /// Converts an axis given in this space to world coordinates.
+    ///
+    /// `orientation` is the model's own orientation and `view` the camera's
+    /// view rotation. Models are not parented, so the parent frame is the
+    /// world frame.
+    pub fn to_world(
+        self,
+        axis: Vector3<f32>,
+        orientation: Quaternion<f32>,
+        view: Quaternion<f32>,
+    ) -> Vector3<f32> {
+        let axis = match self {
+            Space::World | Space::Parent => axis,
+            Space::Local => orientation.rotate_vector(axis),
+            Space::View => view.invert().rotate_vector(axis),
+        };
+        axis.normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{vec3, Deg, Rotation3};
+
+    #[test]
+    fn local_follows_orientation() {
+        let orientation = Quaternion::from_angle_y(Deg(90.));
+        let identity = Quaternion::from_sv(1., vec3(0., 0., 0.));
+        let axis = Space::Local.to_world(vec3(0., 0., 1.), orientation, identity);
+        assert!((axis - vec3(1., 0., 0.)).magnitude() < 1e-5);
+
+        let axis = Space::World.to_world(vec3(0., 0., 1.), orientation, identity);
+        assert!((axis - vec3(0., 0., 1.)).magnitude() < 1e-5);
+    }
+
+    #[test]
+    fn view_uses_inverse_view_rotation() {
+        let identity = Quaternion::from_sv(1., vec3(0., 0., 0.));
+        let view = Quaternion::from_angle_y(Deg(90.));
+        let axis = Space::View.to_world(vec3(0., 0., 1.), identity, view);
+        assert!((axis - vec3(-1., 0., 0.)).magnitude() < 1e-5);
+    }
+}
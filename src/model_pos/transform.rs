@@ -0,0 +1,161 @@
+use cgmath::{vec3, Deg, Euler, InnerSpace, Matrix3, Matrix4, Quaternion, SquareMatrix, Vector3};
+use serde::Deserialize;
+
+/// Translation, rotation and scale as written in config files, with the
+/// rotation given as Euler angles in degrees
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct Transform {
+    pub translation: [f32; 3],
+    pub rotation: [f32; 3],
+    pub scale: [f32; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Transform {
+            translation: [0., 0., 0.],
+            rotation: [0., 0., 0.],
+            scale: [1., 1., 1.],
+        }
+    }
+}
+
+pub fn compose(
+    translation: Vector3<f32>,
+    orientation: Quaternion<f32>,
+    scale: Vector3<f32>,
+) -> Matrix4<f32> {
+    let tmat = Matrix4::from_translation(translation);
+    let omat = Matrix4::from(orientation);
+    let smat = Matrix4::from_nonuniform_scale(scale.x, scale.y, scale.z);
+    tmat * omat * smat
+}
+
+/// Splits an affine matrix into translation, rotation and scale.
+///
+/// Shear is discarded by re-orthogonalizing the rotation, a negative
+/// determinant is moved into the X scale and degenerate axes keep a zero
+/// scale with an identity rotation axis.
+pub fn decompose(m: Matrix4<f32>) -> (Vector3<f32>, Quaternion<f32>, Vector3<f32>) {
+    let translation = m.w.truncate();
+    let (x, y, z) = (m.x.truncate(), m.y.truncate(), m.z.truncate());
+    let mut scale = vec3(x.magnitude(), y.magnitude(), z.magnitude());
+
+    let axis = |v: Vector3<f32>, len: f32, fallback: Vector3<f32>| {
+        if len > f32::EPSILON {
+            v / len
+        } else {
+            fallback
+        }
+    };
+    // A mirrored basis is stored as a negative X scale
+    let mut x = axis(x, scale.x, Vector3::unit_x());
+    if Matrix3::from_cols(m.x.truncate(), m.y.truncate(), m.z.truncate()).determinant() < 0. {
+        scale.x = -scale.x;
+        x = -x;
+    }
+    // Gram-Schmidt, so the result is a proper rotation even with shear
+    let y = y - x * x.dot(y);
+    let len = y.magnitude();
+    let y = if len > f32::EPSILON {
+        y / len
+    } else {
+        // Y is flat or along X, so it's completed from Z, or from the world
+        // axis least aligned with X when Z is flat too
+        let w = m.z.truncate().cross(x);
+        let w = if w.magnitude2() > f32::EPSILON {
+            w
+        } else {
+            let least = (0..3)
+                .min_by(|&a, &b| x[a].abs().partial_cmp(&x[b].abs()).unwrap())
+                .unwrap_or(1);
+            let mut w = Vector3::new(0., 0., 0.);
+            w[least] = 1.;
+            w
+        };
+        (w - x * x.dot(w)).normalize()
+    };
+    let rot = Matrix3::from_cols(x, y, x.cross(y));
+
+    (translation, Quaternion::from(rot).normalize(), scale)
+}
+
+pub fn to_euler_degrees(q: Quaternion<f32>) -> [f32; 3] {
+    let e = Euler::from(q);
+    [Deg::from(e.x).0, Deg::from(e.y).0, Deg::from(e.z).0]
+}
+
+pub fn from_euler_degrees(angles: [f32; 3]) -> Quaternion<f32> {
+    Quaternion::from(Euler::new(Deg(angles[0]), Deg(angles[1]), Deg(angles[2])))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{Rotation3, Transform as _};
+
+    fn assert_close(a: Vector3<f32>, b: Vector3<f32>) {
+        assert!((a - b).magnitude() < 1e-4, "{:?} != {:?}", a, b);
+    }
+
+    #[test]
+    fn round_trip() {
+        let t = vec3(1., -2., 3.);
+        let q = Quaternion::from_axis_angle(vec3(1., 1., 0.).normalize(), Deg(40.));
+        let s = vec3(0.5, 2., 3.);
+
+        let (t2, q2, s2) = decompose(compose(t, q, s));
+        assert_close(t, t2);
+        assert_close(s, s2);
+        assert!(q.dot(q2).abs() > 0.9999);
+    }
+
+    #[test]
+    fn mirrored_round_trip() {
+        let t = vec3(0., 1., 0.);
+        let q = Quaternion::from_angle_y(Deg(30.));
+        let s = vec3(-1., 2., 1.);
+
+        let m = compose(t, q, s);
+        let (t2, q2, s2) = decompose(m);
+        let m2 = compose(t2, q2, s2);
+        let p = cgmath::Point3::new(1., 2., 3.);
+        let (a, b) = (m.transform_point(p), m2.transform_point(p));
+        assert_close(vec3(a.x, a.y, a.z), vec3(b.x, b.y, b.z));
+    }
+
+    #[test]
+    fn degenerate_round_trip() {
+        // A flat model with its X axis pointing up, where Y can't be used to
+        // complete the basis
+        let t = vec3(0., 0., 0.);
+        let q = Quaternion::from_angle_z(Deg(90.));
+        let s = vec3(2., 0., 1.);
+
+        let m = compose(t, q, s);
+        let (_, q2, s2) = decompose(m);
+        assert!((q2.magnitude() - 1.).abs() < 1e-4);
+        let m2 = compose(t, q2, s2);
+        for p in &[vec3(1., 0., 0.), vec3(0., 1., 0.), vec3(0., 0., 1.)] {
+            let (a, b) = (m.transform_vector(*p), m2.transform_vector(*p));
+            assert_close(a, b);
+        }
+
+        // Flat along Y and Z, any rotation keeping X works
+        let m = compose(t, q, vec3(2., 0., 0.));
+        let (_, q2, s2) = decompose(m);
+        assert!((q2.magnitude() - 1.).abs() < 1e-4);
+        let x = compose(t, q2, s2).transform_vector(vec3(1., 0., 0.));
+        assert_close(m.transform_vector(vec3(1., 0., 0.)), x);
+    }
+
+    #[test]
+    fn euler_round_trip() {
+        let angles = [10., -35., 70.];
+        let back = to_euler_degrees(from_euler_degrees(angles));
+        for i in 0..3 {
+            assert!((angles[i] - back[i]).abs() < 1e-3);
+        }
+    }
+}
@@ -0,0 +1,124 @@
+use gl::{self, types::*};
+
+use failure::ensure;
+use std::os::raw::c_void;
+
+/// Framebuffer object with a color and a depth renderbuffer, used to draw
+/// without a visible window
+pub struct Framebuffer {
+    fbo: u32,
+    color: u32,
+    depth: u32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Framebuffer {
+    pub fn new(width: u32, height: u32) -> Result<Framebuffer, failure::Error> {
+        let mut max_size: GLint = 0;
+        unsafe { gl::GetIntegerv(gl::MAX_RENDERBUFFER_SIZE, &mut max_size) };
+        ensure!(
+            width > 0 && height > 0 && width.max(height) <= max_size as u32,
+            "Offscreen size {}x{} should be between 1 and {}",
+            width,
+            height,
+            max_size
+        );
+
+        let mut fb = Framebuffer {
+            fbo: 0,
+            color: 0,
+            depth: 0,
+            width,
+            height,
+        };
+        let status = unsafe {
+            gl::GenFramebuffers(1, &mut fb.fbo);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, fb.fbo);
+
+            gl::GenRenderbuffers(1, &mut fb.color);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, fb.color);
+            gl::RenderbufferStorage(gl::RENDERBUFFER, gl::RGBA8, width as i32, height as i32);
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                fb.color,
+            );
+
+            gl::GenRenderbuffers(1, &mut fb.depth);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, fb.depth);
+            gl::RenderbufferStorage(
+                gl::RENDERBUFFER,
+                gl::DEPTH24_STENCIL8,
+                width as i32,
+                height as i32,
+            );
+            gl::FramebufferRenderbuffer(
+                gl::FRAMEBUFFER,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                fb.depth,
+            );
+
+            let status = gl::CheckFramebufferStatus(gl::FRAMEBUFFER);
+            gl::BindRenderbuffer(gl::RENDERBUFFER, 0);
+            gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+            status
+        };
+        ensure!(
+            status == gl::FRAMEBUFFER_COMPLETE,
+            "Offscreen framebuffer is incomplete, status {:#x}",
+            status
+        );
+        Ok(fb)
+    }
+
+    /// Sends the following draws to this framebuffer
+    pub unsafe fn bind(&self) {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, self.fbo);
+    }
+
+    /// Sends the following draws back to the window
+    pub unsafe fn unbind() {
+        gl::BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+
+    pub unsafe fn read_pixels(&self) -> Vec<u8> {
+        read_pixels(self.width, self.height)
+    }
+}
+
+impl Drop for Framebuffer {
+    fn drop(&mut self) {
+        unsafe {
+            gl::DeleteRenderbuffers(1, &self.depth);
+            gl::DeleteRenderbuffers(1, &self.color);
+            gl::DeleteFramebuffers(1, &self.fbo);
+        }
+    }
+}
+
+/// RGBA pixels of the bound framebuffer, starting at the top left corner
+/// like image files do
+pub unsafe fn read_pixels(width: u32, height: u32) -> Vec<u8> {
+    let row = width as usize * 4;
+    let mut pixels = vec![0u8; row * height as usize];
+    gl::PixelStorei(gl::PACK_ALIGNMENT, 1);
+    gl::ReadPixels(
+        0,
+        0,
+        width as i32,
+        height as i32,
+        gl::RGBA,
+        gl::UNSIGNED_BYTE,
+        pixels.as_mut_ptr() as *mut c_void,
+    );
+
+    // OpenGL rows start at the bottom
+    pixels
+        .chunks(row)
+        .rev()
+        .flat_map(|r| r.iter().cloned())
+        .collect()
+}
@@ -0,0 +1,198 @@
+use crate::{bounds::Aabb, mesh::Vertex, viewport::Viewport};
+
+use cgmath::{vec4, InnerSpace, Matrix4, SquareMatrix, Vector3};
+
+/// Half line starting at `origin`. `direction` isn't normalized, so distances
+/// are given as multiples of its length.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Vector3<f32>,
+    pub direction: Vector3<f32>,
+}
+
+impl Ray {
+    pub fn new(origin: Vector3<f32>, direction: Vector3<f32>) -> Ray {
+        Ray { origin, direction }
+    }
+
+    /// Ray through the framebuffer pixel `(x, y)` of `viewport`, with the
+    /// origin at the bottom left corner, going from the near to the far
+    /// plane of `projection`
+    pub fn from_pixel(
+        projection: &Matrix4<f32>,
+        view: &Matrix4<f32>,
+        viewport: &Viewport,
+        x: f32,
+        y: f32,
+    ) -> Option<Ray> {
+        let inverse = (projection * view).invert()?;
+        let ndc_x = 2. * (x - viewport.x as f32) / viewport.width.max(1) as f32 - 1.;
+        let ndc_y = 2. * (y - viewport.y as f32) / viewport.height.max(1) as f32 - 1.;
+        let unproject = |z| {
+            let p = inverse * vec4(ndc_x, ndc_y, z, 1.);
+            p.truncate() / p.w
+        };
+        let (near, far) = (unproject(-1.), unproject(1.));
+        Some(Ray::new(near, (far - near).normalize()))
+    }
+
+    /// The same ray in the space `m` maps to, distances along both rays match
+    pub fn transformed(&self, m: &Matrix4<f32>) -> Ray {
+        Ray::new(
+            (m * self.origin.extend(1.)).truncate(),
+            (m * self.direction.extend(0.)).truncate(),
+        )
+    }
+
+    pub fn at(&self, t: f32) -> Vector3<f32> {
+        self.origin + self.direction * t
+    }
+
+    /// Distance to where the ray enters `aabb`, 0 when it starts inside
+    pub fn intersect_aabb(&self, aabb: &Aabb) -> Option<f32> {
+        let (mut near, mut far) = (0f32, f32::INFINITY);
+        for i in 0..3 {
+            let (o, d) = (self.origin[i], self.direction[i]);
+            if d == 0. {
+                if o < aabb.min[i] || o > aabb.max[i] {
+                    return None;
+                }
+                continue;
+            }
+            let (t0, t1) = ((aabb.min[i] - o) / d, (aabb.max[i] - o) / d);
+            near = near.max(t0.min(t1));
+            far = far.min(t0.max(t1));
+        }
+        if near <= far {
+            Some(near)
+        } else {
+            None
+        }
+    }
+
+    /// Moller-Trumbore intersection, hitting both sides of the triangle
+    pub fn intersect_triangle(
+        &self,
+        a: Vector3<f32>,
+        b: Vector3<f32>,
+        c: Vector3<f32>,
+    ) -> Option<f32> {
+        let (e1, e2) = (b - a, c - a);
+        let p = self.direction.cross(e2);
+        let det = e1.dot(p);
+        if det.abs() < f32::EPSILON {
+            return None;
+        }
+        let s = self.origin - a;
+        let u = s.dot(p) / det;
+        if !(0. ..=1.).contains(&u) {
+            return None;
+        }
+        let q = s.cross(e1);
+        let v = self.direction.dot(q) / det;
+        if v < 0. || u + v > 1. {
+            return None;
+        }
+        let t = e2.dot(q) / det;
+        if t >= 0. {
+            Some(t)
+        } else {
+            None
+        }
+    }
+
+    /// Nearest hit with the triangles of a mesh placed by `model`, skipping
+    /// the triangles when its bounding box is missed
+    pub fn intersect_mesh(
+        &self,
+        model: &Matrix4<f32>,
+        aabb: &Aabb,
+        vertices: &[Vertex],
+        indices: &[u32],
+    ) -> Option<f32> {
+        let ray = self.transformed(&model.invert()?);
+        ray.intersect_aabb(aabb)?;
+        indices
+            .chunks(3)
+            .filter(|t| t.len() == 3)
+            .filter_map(|t| {
+                let p = |i: u32| vertices.get(i as usize).map(|v| v.position);
+                ray.intersect_triangle(p(t[0])?, p(t[1])?, p(t[2])?)
+            })
+            .fold(None, |nearest: Option<f32>, t| {
+                Some(nearest.map_or(t, |n| n.min(t)))
+            })
+    }
+}
+
+/// Index of the nearest of `hits`, each one being the distance to an object
+/// or `None` if it was missed
+pub fn nearest<I>(hits: I) -> Option<usize>
+where
+    I: IntoIterator<Item = Option<f32>>,
+{
+    hits.into_iter()
+        .enumerate()
+        .filter_map(|(i, t)| t.map(|t| (i, t)))
+        .fold(
+            None,
+            |nearest: Option<(usize, f32)>, (i, t)| match nearest {
+                Some((_, n)) if n <= t => nearest,
+                _ => Some((i, t)),
+            },
+        )
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cgmath::{perspective, vec3, Deg, Point3};
+    use pretty_assertions::assert_eq;
+
+    /// Square on the XY plane, from -1 to 1
+    fn square() -> (Aabb, Vec<Vertex>, Vec<u32>) {
+        let vertices: Vec<_> = [(-1., -1.), (1., -1.), (1., 1.), (-1., 1.)]
+            .iter()
+            .map(|&(x, y)| Vertex {
+                position: vec3(x, y, 0.),
+                ..Vertex::default()
+            })
+            .collect();
+        let aabb = Aabb::from_points(vertices.iter().map(|v| v.position));
+        (aabb, vertices, vec![0, 1, 2, 0, 2, 3])
+    }
+
+    #[test]
+    fn pick_nearest_square() {
+        let view = Matrix4::look_at(
+            Point3::new(0., 0., 10.),
+            Point3::new(0., 0., 0.),
+            vec3(0., 1., 0.),
+        );
+        let projection = perspective(Deg(45.), 1., 0.1, 100.);
+        let viewport = Viewport::new(0, 0, 100, 100);
+        let ray = Ray::from_pixel(&projection, &view, &viewport, 50., 50.).unwrap();
+        assert!((ray.direction - vec3(0., 0., -1.)).magnitude() < 1e-5);
+
+        let (aabb, vertices, indices) = square();
+        let models = [
+            Matrix4::from_translation(vec3(0., 0., -5.)),
+            Matrix4::from_translation(vec3(0., 0., 2.)) * Matrix4::from_scale(2.),
+            Matrix4::from_translation(vec3(5., 0., 0.)),
+        ];
+        let hits: Vec<_> = models
+            .iter()
+            .map(|m| ray.intersect_mesh(m, &aabb, &vertices, &indices))
+            .collect();
+        let hit = ray.at(hits[1].unwrap());
+        assert!((hit - vec3(0., 0., 2.)).magnitude() < 1e-3);
+        assert_eq!(None, hits[2]);
+        assert_eq!(Some(1), nearest(hits));
+
+        // the corner of the viewport misses the square at the origin
+        let ray = Ray::from_pixel(&projection, &view, &viewport, 0., 0.).unwrap();
+        let m = Matrix4::from_scale(1.);
+        assert_eq!(None, ray.intersect_mesh(&m, &aabb, &vertices, &indices));
+    }
+}
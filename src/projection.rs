@@ -0,0 +1,159 @@
+use cgmath::{ortho, perspective, Deg, Matrix4};
+use failure::ensure;
+use serde::{Deserialize, Serialize};
+
+pub const FOV: f32 = 45.;
+pub const NEAR: f32 = 0.1;
+pub const FAR: f32 = 100.;
+pub const HEIGHT: f32 = 10.;
+
+const MIN_FOV: f32 = 1.;
+const MAX_FOV: f32 = 120.;
+const MIN_HEIGHT: f32 = 0.1;
+const HEIGHT_ZOOM: f32 = 0.1;
+
+#[derive(Copy, Clone, Debug, Deserialize, Serialize, PartialEq)]
+pub enum Projection {
+    /// `fov` is the vertical field of view in degrees
+    Perspective { fov: f32, near: f32, far: f32 },
+    /// `height` is the vertical extent of the view volume in world units
+    Orthographic { height: f32, near: f32, far: f32 },
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Projection::Perspective {
+            fov: FOV,
+            near: NEAR,
+            far: FAR,
+        }
+    }
+}
+
+impl Projection {
+    /// Fails for a projection that can't make a proper matrix, or a field
+    /// of view zooming would snap back into range
+    pub fn check(&self) -> Result<(), failure::Error> {
+        let (near, far) = match *self {
+            Projection::Perspective { fov, near, far } => {
+                ensure!(
+                    (MIN_FOV..=MAX_FOV).contains(&fov),
+                    "Field of view should be between {} and {} degrees",
+                    MIN_FOV,
+                    MAX_FOV
+                );
+                (near, far)
+            }
+            Projection::Orthographic { height, near, far } => {
+                ensure!(height > 0., "Orthographic height should be positive");
+                (near, far)
+            }
+        };
+        ensure!(near > 0., "Near plane should be in front of the camera");
+        ensure!(far > near, "Far plane should be beyond the near plane");
+        Ok(())
+    }
+
+    pub fn matrix(&self, aspect: f32) -> Matrix4<f32> {
+        match *self {
+            Projection::Perspective { fov, near, far } => perspective(Deg(fov), aspect, near, far),
+            Projection::Orthographic { height, near, far } => {
+                let (w, h) = (height * aspect / 2., height / 2.);
+                ortho(-w, w, -h, h, near, far)
+            }
+        }
+    }
+
+    /// Switches to the other kind of projection, keeping the apparent size of
+    /// objects at `distance` from the camera
+    pub fn toggled(&self, distance: f32) -> Self {
+        match *self {
+            Projection::Perspective { fov, near, far } => Projection::Orthographic {
+                height: 2. * distance * (fov.to_radians() / 2.).tan(),
+                near,
+                far,
+            },
+            Projection::Orthographic { height, near, far } => Projection::Perspective {
+                fov: (2. * (height / 2. / distance).atan().to_degrees())
+                    .max(MIN_FOV)
+                    .min(MAX_FOV),
+                near,
+                far,
+            },
+        }
+    }
+
+    /// Narrows the field of view, or the orthographic height, by `amount`
+    pub fn zoom(&mut self, amount: f32) {
+        match self {
+            Projection::Perspective { fov, .. } => {
+                *fov = (*fov - amount).max(MIN_FOV).min(MAX_FOV);
+            }
+            Projection::Orthographic { height, .. } => {
+                *height = (*height * (1. - amount * HEIGHT_ZOOM)).max(MIN_HEIGHT);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_round_trip() {
+        let p = Projection::default();
+        let back = p.toggled(20.).toggled(20.);
+        match back {
+            Projection::Perspective { fov, .. } => assert!((fov - FOV).abs() < 1e-3),
+            _ => panic!("expected a perspective projection"),
+        }
+    }
+
+    #[test]
+    fn zoom_is_clamped() {
+        let mut p = Projection::Perspective {
+            fov: 60.,
+            near: NEAR,
+            far: FAR,
+        };
+        let fov = |p: &Projection| match *p {
+            Projection::Perspective { fov, .. } => fov,
+            _ => panic!("expected a perspective projection"),
+        };
+        assert!(p.check().is_ok());
+        p.zoom(0.);
+        assert_eq!(60., fov(&p));
+        p.zoom(-100.);
+        assert_eq!(MAX_FOV, fov(&p));
+        p.zoom(1000.);
+        assert_eq!(MIN_FOV, fov(&p));
+
+        let wide = Projection::Perspective {
+            fov: 150.,
+            near: NEAR,
+            far: FAR,
+        };
+        assert!(wide.check().is_err());
+    }
+
+    #[test]
+    fn degenerate_projections() {
+        let perspective = |near, far| Projection::Perspective {
+            fov: FOV,
+            near,
+            far,
+        };
+        assert!(perspective(NEAR, FAR).check().is_ok());
+        assert!(perspective(0., FAR).check().is_err());
+        assert!(perspective(-1., FAR).check().is_err());
+        assert!(perspective(NEAR, NEAR).check().is_err());
+
+        let flat = Projection::Orthographic {
+            height: 0.,
+            near: NEAR,
+            far: FAR,
+        };
+        assert!(flat.check().is_err());
+    }
+}
@@ -1,51 +1,210 @@
-use crate::{camera::Camera, model::Model, model_pos::ModelPosition, shader::Shader};
+use crate::{
+    bindings::{Action, Bindings},
+    bookmark::Bookmarks,
+    bounds::Frustum,
+    camera::{Camera, CameraMode},
+    camera_path::CameraPath,
+    capture::{self, Recording},
+    gamepad::{Gamepad, GamepadConfig},
+    input::Input,
+    model::Model,
+    model_pos::{Group, ModelPosition, Surroundings},
+    offscreen::{self, Framebuffer},
+    picking::{self, Ray},
+    projection::Projection,
+    scene_file::SceneFile,
+    shader::Shader,
+    time_control::{self, TimeControl},
+    viewport::Layout,
+};
 
 use gl;
 use glfw::{self, Context};
+use image;
 
-use cgmath::{perspective, vec3, Deg, Matrix4};
+use cgmath::{vec3, Matrix4, Vector3};
 use failure::ensure;
 
-use std::{path::Path, sync::mpsc::Receiver};
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::Receiver,
+};
+
+const PIVOT_MARKER_SCALE: f32 = 0.25;
+/// Seconds the step key is held before it steps every frame
+const REPEAT_DELAY: f32 = 0.5;
+/// Screen coordinates the cursor can move between pressing and releasing
+/// the left button for it to still pick, instead of only dragging
+const CLICK_DISTANCE: f64 = 4.;
 
 pub struct Scene {
     glfw: glfw::Glfw,
     window: glfw::Window,
     events: Receiver<(f64, glfw::WindowEvent)>,
 
-    camera: Camera,
+    cameras: Vec<Camera>,
+    /// Index of the camera receiving input, viewport `i` shows camera `i`
+    active_camera: usize,
+    layout: Layout,
+    bindings: Bindings,
+    gamepad: Gamepad,
+    /// Keys and mouse of the current frame, the mouse movement is gathered
+    /// from the events
+    input: Input,
+    /// Cursor position when the left button went down
+    click: Option<(f64, f64)>,
+    /// Name being typed to select models, keys only type while it's open
+    typed_name: Option<String>,
+    /// Time scale of the models, cameras always move in real time
+    time: TimeControl,
+    /// Save the next frame to a PNG file, requested with `F12`
+    screenshot: bool,
+    /// Frames are written here while recording, toggled with `F10`
+    recording: Option<Recording>,
+    record_path: PathBuf,
+    record_fps: f32,
+    /// Print how many meshes are culled whenever it changes, toggled with `B`
+    show_culling: bool,
+    bookmarks: Bookmarks,
+    /// File the bookmarks are saved to, next to the models config
+    bookmarks_path: PathBuf,
+    /// Framebuffer size in pixels
     wscreen: u32,
     hscreen: u32,
+    /// Framebuffer pixels per screen coordinate, above 1 on HiDPI displays
+    scale: f32,
+    /// Index of the monitor used in fullscreen
+    monitor: usize,
+    /// Position and size to restore when leaving fullscreen
+    windowed: (i32, i32, u32, u32),
 
-    shader: Shader,
-    model: Model,
-    models: Vec<ModelPosition>,
+    shaders: Vec<Shader>,
+    /// Loaded once and shared by all their instances
+    models: Vec<Model>,
+    /// Names of `models` in the scene file
+    model_names: Vec<String>,
+    instances: Vec<Instance>,
+    /// Placement of new instances, from the models config
+    default_position: ModelPosition,
+    default_shader: usize,
+    /// Index in `models` of the model added by `Spawn`
+    spawn_model: usize,
 
-    axis_m: Model,
+    /// Index in `models` of the model drawn at the origin and on the pivots
+    axis_m: usize,
     axis_p: ModelPosition,
 }
 
+/// Placement of one of the scene models, drawn with one of its shaders
+#[derive(Clone)]
+pub(crate) struct Instance {
+    pub name: String,
+    pub model: usize,
+    pub shader: usize,
+    pub position: ModelPosition,
+}
+
+/// Options used to build a `Scene`, usually coming from the command line
+#[derive(Debug)]
+pub struct Settings {
+    pub wscreen: u32,
+    pub hscreen: u32,
+    /// Number of models of the default scene, used without `scene`
+    pub n_models: usize,
+    /// Configuration of the models that don't give one
+    pub models_config: PathBuf,
+    pub scene: Option<PathBuf>,
+    /// Names of the models selected at start, instead of the scene defaults
+    pub select: Vec<String>,
+    pub sensitivity: f32,
+    pub camera_speed: f32,
+    pub projection: Projection,
+    /// Flythrough played by the main camera, toggled with `Y`
+    pub camera_path: Option<PathBuf>,
+    /// Start in fullscreen, toggled with `F11`
+    pub fullscreen: bool,
+    /// Index of the monitor used in fullscreen, 0 is the primary monitor
+    pub monitor: usize,
+    pub vsync: bool,
+    /// Show the window, hidden windows are used to render offscreen
+    pub visible: bool,
+    /// Start recording to this `.y4m` file or image directory, `F10` records
+    /// to `recording` when missing
+    pub record: Option<PathBuf>,
+    pub record_fps: f32,
+    /// Key bindings replacing some of the defaults
+    pub bindings: Option<PathBuf>,
+    /// Gamepad axes and buttons, an XInput controller when missing
+    pub gamepad: Option<PathBuf>,
+}
+
 pub trait SceneObject {
-    fn process_input(&mut self, window: &glfw::Window, delta_time: f32);
+    fn process_input(&mut self, input: &Input, bindings: &Bindings, delta_time: f32);
 }
 
 impl Scene {
-    pub fn init<P>(
-        wscreen: u32,
-        hscreen: u32,
-        n_models: usize,
-        models_config: P,
-    ) -> Result<Self, failure::Error>
-    where
-        P: AsRef<Path>,
-    {
-        ensure!(
-            n_models > 0 && n_models < 10,
-            "Number of models should be bigger than 0 and lower than 10"
-        );
+    pub fn init(settings: Settings) -> Result<Self, failure::Error> {
+        let Settings {
+            wscreen,
+            hscreen,
+            n_models,
+            models_config,
+            scene,
+            select,
+            sensitivity,
+            camera_speed,
+            projection,
+            camera_path,
+            fullscreen,
+            monitor,
+            vsync,
+            visible,
+            record,
+            record_fps,
+            bindings,
+            gamepad,
+        } = settings;
 
-        let mut camera = Camera::default();
-        camera.model_pos.translation = vec3(0., 1., 20.);
+        let scene = match scene {
+            Some(path) => SceneFile::from_path(path)?,
+            None => SceneFile::with_models(n_models),
+        };
+        let resolved = scene.resolve()?;
+        resolved.check_files()?;
+        let bindings = match bindings {
+            Some(path) => Bindings::from_path(path)?,
+            None => Bindings::default(),
+        };
+        let gamepad = match gamepad {
+            Some(path) => Gamepad::new(GamepadConfig::from_path(path)?)?,
+            None => Gamepad::new(GamepadConfig::default())?,
+        };
+
+        let mut cameras = Vec::with_capacity(scene.cameras.len());
+        for c in &scene.cameras {
+            let mut camera = Camera::default();
+            camera.name = c.name.clone();
+            camera.model_pos.translation = c.position.into();
+            camera.sensitivity = sensitivity;
+            camera.speed = camera_speed;
+            camera.projection = c.projection.unwrap_or(projection);
+            camera.projection.check()?;
+            if let Some(p) = c.look_at {
+                camera.look_at(p.into());
+            }
+            if let Some(view) = c.view {
+                // orbit on the preset like a modelling tool quad view
+                camera.toggle_mode();
+                camera.set_view(view);
+            }
+            if let Some(path) = &c.path {
+                camera.path = Some(CameraPath::from_path(path)?);
+            }
+            cameras.push(camera);
+        }
+        if let Some(path) = camera_path {
+            cameras[0].path = Some(CameraPath::from_path(path)?);
+        }
 
         // glfw: initialize and configure
         // ------------------------------
@@ -56,85 +215,163 @@ impl Scene {
         ));
         #[cfg(target_os = "macos")]
         glfw.window_hint(glfw::WindowHint::OpenGlForwardCompat(true));
+        glfw.window_hint(glfw::WindowHint::Visible(visible));
 
         // glfw window creation
         // --------------------
+        let n_monitors = glfw.with_connected_monitors(|_, monitors| monitors.len());
+        ensure!(
+            monitor < n_monitors,
+            "Monitor {} not found, there are {} monitors connected",
+            monitor,
+            n_monitors
+        );
         let (mut window, events) = glfw
-            .create_window(wscreen, hscreen, "LearnOpenGL", glfw::WindowMode::Windowed)
+            .with_connected_monitors(|glfw, monitors| {
+                let m = &monitors[monitor];
+                match m.get_video_mode() {
+                    Some(mode) if fullscreen => glfw.create_window(
+                        mode.width,
+                        mode.height,
+                        "LearnOpenGL",
+                        glfw::WindowMode::FullScreen(m),
+                    ),
+                    _ => glfw.create_window(
+                        wscreen,
+                        hscreen,
+                        "LearnOpenGL",
+                        glfw::WindowMode::Windowed,
+                    ),
+                }
+            })
             .expect("Failed to create GLFW window");
 
         window.make_current();
+        glfw.set_swap_interval(if vsync {
+            glfw::SwapInterval::Sync(1)
+        } else {
+            glfw::SwapInterval::None
+        });
         window.set_framebuffer_size_polling(true);
         window.set_cursor_pos_polling(true);
         window.set_scroll_polling(true);
+        window.set_key_polling(true);
+        window.set_char_polling(true);
+        window.set_mouse_button_polling(true);
 
         // tell GLFW to capture our mouse
         window.set_cursor_mode(glfw::CursorMode::Disabled);
 
+        // on HiDPI displays the framebuffer is bigger than the window
+        let (width, height) = window.get_framebuffer_size();
+        let (x, y) = window.get_pos();
+        let windowed = (x, y, wscreen, hscreen);
+        let (wscreen, hscreen) = (width as u32, height as u32);
+        let scale = width as f32 / window.get_size().0.max(1) as f32;
+
         // gl: load all OpenGL function pointers
         // ---------------------------------------
         gl::load_with(|symbol| window.get_proc_address(symbol) as *const _);
 
-        let (shader, model) = unsafe {
-            // configure global opengl state
-            // -----------------------------
-            gl::Enable(gl::DEPTH_TEST);
+        // configure global opengl state
+        // -----------------------------
+        unsafe { gl::Enable(gl::DEPTH_TEST) };
 
-            // build and compile shaders
-            // -------------------------
-            let our_shader = Shader::new("resources/cg_ufpel.vs", "resources/cg_ufpel.fs");
-
-            // load models
-            // -----------
-            let our_model = Model::new("resources/objects/axis_arrows/axis_arrows.obj");
+        // build and compile shaders
+        // -------------------------
+        let shaders = resolved
+            .shaders
+            .iter()
+            .map(|s| Shader::new(&s.vertex, &s.fragment))
+            .collect();
 
-            (our_shader, our_model)
-        };
+        // load models
+        // -----------
+        let models = resolved.models.iter().map(|m| Model::new(m)).collect();
 
-        let axis_m = Model::new("resources/objects/axis_arrows/axis_arrows.obj");
+        let axis_m = resolved.axis;
         let axis_p = ModelPosition::default();
 
-        let mut x_offset = 0.;
-        let mut models: Vec<_> = std::iter::repeat(ModelPosition::with_config(models_config)?)
-            .take(n_models)
-            .map(|mut m| {
-                m.translation.x = x_offset;
-                x_offset += 2.;
-                m
-            })
-            .collect();
-        models[0].is_selected = true;
+        let bookmarks_path = models_config.with_file_name("bookmarks.json");
+        // a broken file shouldn't keep the scene from starting, storing a
+        // bookmark writes a valid one again
+        let bookmarks = Bookmarks::from_path(&bookmarks_path).unwrap_or_else(|e| {
+            eprintln!("Ignoring {}: {}", bookmarks_path.display(), e);
+            Bookmarks::default()
+        });
 
-        Ok(Scene {
+        let default_position = ModelPosition::with_config(models_config)?;
+        let mut instances = Vec::with_capacity(scene.instances.len());
+        for (i, (name, model, shader)) in scene.instances.iter().zip(resolved.instances) {
+            instances.push(Instance {
+                name,
+                model,
+                shader,
+                position: i.position(&default_position)?,
+            });
+        }
+
+        let mut scene = Scene {
             glfw,
             window,
             events,
 
-            camera,
+            cameras,
+            active_camera: 0,
+            layout: Layout::default(),
+            bindings,
+            gamepad,
+            input: Input::default(),
+            click: None,
+            typed_name: None,
+            time: TimeControl::default(),
+            screenshot: false,
+            recording: None,
+            record_path: record.clone().unwrap_or_else(|| PathBuf::from("recording")),
+            record_fps,
+            show_culling: false,
+            bookmarks,
+            bookmarks_path,
             wscreen,
             hscreen,
+            scale,
+            monitor,
+            windowed,
 
-            shader,
-            model,
+            shaders,
             models,
+            model_names: scene.models.keys().cloned().collect(),
+            instances,
+            default_position,
+            default_shader: resolved.shader,
+            spawn_model: 0,
 
             axis_m,
             axis_p,
-        })
+        };
+        // start with the first model that isn't the axis
+        scene.spawn_model = scene.next_spawn_model(scene.models.len() - 1);
+        if !select.is_empty() {
+            scene.set_selection(|_| false);
+        }
+        if record.is_some() {
+            scene.toggle_recording();
+        }
+        for name in &select {
+            ensure!(
+                scene.select_by_name(name, true),
+                "No model named {:?}",
+                name
+            );
+        }
+        Ok(scene)
     }
 
     pub fn run(&mut self) -> Result<(), failure::Error> {
-        // Camera data
-        let mut first_mouse = true;
-        let mut last_x: f32 = self.wscreen as f32 / 2.;
-        let mut last_y: f32 = self.hscreen as f32 / 2.;
-
         // timing
         let mut delta_time: f32; // time between current frame and last frame
         let mut last_frame: f32 = 0.;
-
-        // don't forget to enable shader before setting uniforms
-        unsafe { self.shader.use_program() };
+        let mut last_culling = Vec::new();
 
         // render loop
         // -----------
@@ -144,33 +381,26 @@ impl Scene {
             let current_frame = self.glfw.get_time() as f32;
             delta_time = current_frame - last_frame;
             last_frame = current_frame;
+            // recordings advance a fixed time per frame, however long it
+            // takes to draw and save it
+            if let Some(recording) = &self.recording {
+                delta_time = 1. / recording.fps;
+            }
 
-            self.process_events(&mut first_mouse, &mut last_x, &mut last_y);
+            self.process_events();
 
             self.process_input(delta_time);
 
             unsafe {
-                gl::ClearColor(0.1, 0.1, 0.1, 1.);
-                gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-
-                // view/projection transformations
-                let projection: Matrix4<f32> = perspective(
-                    Deg(self.camera.zoom),
-                    self.wscreen as f32 / self.hscreen as f32,
-                    0.1,
-                    100.,
-                );
-                let view = self.camera.get_view_matrix();
-                self.shader.set_mat4(c_str!("projection"), &projection);
-                self.shader.set_mat4(c_str!("view"), &view);
-
-                self.models.iter().for_each(|m| {
-                    self.shader.set_mat4(c_str!("model"), &m.matrix());
-                    self.model.draw(&self.shader);
-                });
-                self.shader.set_mat4(c_str!("model"), &self.axis_p.matrix());
-                self.axis_m.draw(&self.shader);
+                let culling = self.draw_viewports(self.wscreen, self.hscreen);
+                if self.show_culling && culling != last_culling {
+                    for ((drawn, culled), camera) in culling.iter().zip(&self.cameras) {
+                        println!("{}: drawn {} culled {}", camera.name, drawn, culled);
+                    }
+                }
+                last_culling = culling;
             }
+            self.capture();
 
             // glfw: swap buffers and poll IO events (keys pressed/released, mouse moved
             // etc.)
@@ -182,137 +412,764 @@ impl Scene {
         Ok(())
     }
 
+    /// Runs the simulation for `seconds` in fixed steps, without waiting
+    /// and without reading the keyboard, mouse or gamepad
+    pub fn advance(&mut self, seconds: f32) {
+        self.input = Input::default();
+        let mut time = 0.;
+        while time < seconds {
+            let step = time_control::STEP.min(seconds - time);
+            self.update(step);
+            time += step;
+        }
+    }
+
+    /// Starts the command list animation of every model
+    pub fn start_animations(&mut self) {
+        self.instances
+            .iter_mut()
+            .for_each(|i| i.position.start_animation());
+    }
+
+    /// Draws a frame at `width` x `height` into an offscreen framebuffer and
+    /// saves it, the image format comes from the extension of `path`
+    pub fn render_to_file<P>(
+        &mut self,
+        path: P,
+        width: u32,
+        height: u32,
+    ) -> Result<(), failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let framebuffer = Framebuffer::new(width, height)?;
+        let pixels = unsafe {
+            framebuffer.bind();
+            self.draw_viewports(width, height);
+            let pixels = framebuffer.read_pixels();
+            Framebuffer::unbind();
+            gl::Viewport(0, 0, self.wscreen as i32, self.hscreen as i32);
+            pixels
+        };
+        image::save_buffer(path, &pixels, width, height, image::ColorType::RGBA(8))?;
+        Ok(())
+    }
+
+    /// Reads back the frame just drawn when a screenshot was requested or
+    /// while recording
+    fn capture(&mut self) {
+        if !self.screenshot && self.recording.is_none() {
+            return;
+        }
+        let (width, height) = (self.wscreen, self.hscreen);
+        let pixels = unsafe { offscreen::read_pixels(width, height) };
+
+        if self.screenshot {
+            self.screenshot = false;
+            match capture::screenshot(&pixels, width, height) {
+                Ok(path) => println!("Saved {}", path.display()),
+                Err(e) => eprintln!("Failed to save screenshot: {}", e),
+            }
+        }
+        if let Some(recording) = &mut self.recording {
+            if let Err(e) = recording.write_frame(&pixels, width, height) {
+                eprintln!("Stopped recording: {}", e);
+                self.recording = None;
+            }
+        }
+    }
+
+    fn toggle_recording(&mut self) {
+        match self.recording.take() {
+            Some(recording) => println!(
+                "Recorded {} frames to {}",
+                recording.frames(),
+                self.record_path.display()
+            ),
+            None => match Recording::new(&self.record_path, self.record_fps) {
+                Ok(recording) => {
+                    println!("Recording to {}", self.record_path.display());
+                    self.recording = Some(recording);
+                }
+                Err(e) => eprintln!("Failed to start recording: {}", e),
+            },
+        }
+    }
+
+    /// Clears the framebuffer and draws each viewport of the layout, returning
+    /// the meshes drawn and culled on each one
+    unsafe fn draw_viewports(&self, width: u32, height: u32) -> Vec<(usize, usize)> {
+        gl::ClearColor(0.1, 0.1, 0.1, 1.);
+        gl::Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+
+        let viewports = self.layout.viewports(width, height);
+        let mut culling = Vec::with_capacity(viewports.len());
+        for (viewport, camera) in viewports.iter().zip(&self.cameras) {
+            gl::Viewport(
+                viewport.x,
+                viewport.y,
+                viewport.width as i32,
+                viewport.height as i32,
+            );
+            culling.push(self.draw(camera, viewport.aspect()));
+        }
+        culling
+    }
+
+    /// Draws the scene seen by `camera`, returning how many model meshes were
+    /// drawn and culled
+    unsafe fn draw(&self, camera: &Camera, aspect: f32) -> (usize, usize) {
+        // view/projection transformations
+        let projection: Matrix4<f32> = camera.projection_matrix(aspect);
+        let view = camera.get_view_matrix();
+        for shader in &self.shaders {
+            // don't forget to enable shader before setting uniforms
+            shader.use_program();
+            shader.set_mat4(c_str!("projection"), &projection);
+            shader.set_mat4(c_str!("view"), &view);
+        }
+
+        let frustum = Frustum::from_matrix(&(projection * view));
+        let counts = self.instances.iter().fold((0, 0), |(drawn, culled), i| {
+            let (shader, matrix) = (&self.shaders[i.shader], i.position.matrix());
+            shader.use_program();
+            shader.set_mat4(c_str!("model"), &matrix);
+            let (d, c) = self.models[i.model].draw_culled(shader, &matrix, &frustum);
+            (drawn + d, culled + c)
+        });
+
+        // the axis and the pivot markers use the default shader
+        let (shader, axis) = (
+            &self.shaders[self.default_shader],
+            &self.models[self.axis_m],
+        );
+        shader.use_program();
+        shader.set_mat4(c_str!("model"), &self.axis_p.matrix());
+        axis.draw(shader);
+
+        // mark the pivot of the selected models
+        let translations = self.translations();
+        let scene = Surroundings {
+            translations: &translations,
+            selection_centroid: self.selection_centroid(),
+        };
+        self.selected().for_each(|m| {
+            let marker = Matrix4::from_translation(m.pivot_point(&scene))
+                * Matrix4::from_scale(PIVOT_MARKER_SCALE);
+            shader.set_mat4(c_str!("model"), &marker);
+            axis.draw(shader);
+        });
+        counts
+    }
+
     fn process_input(&mut self, delta_time: f32) {
         unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, gl::FILL) };
+        self.input.poll(&self.window, delta_time);
+        self.input.update_gamepad(self.gamepad.poll(&self.glfw));
+        if self.typed_name.is_some() {
+            self.type_name();
+        } else {
+            self.run_actions();
+        }
+        self.update(delta_time);
+    }
 
-        process_keys!(
-            self.window;
-            glfw::Key::Escape, glfw::Action::Press => self.window.set_should_close(true),
-            glfw::Key::T, glfw::Action::Press => {
-                // draw in wireframe
-                unsafe{gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE)};
-            },
-            glfw::Key::Num1, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 0 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num2, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 1 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num3, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 2 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num4, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 3 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num5, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 4 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num6, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 5 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num7, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 6 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num8, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 7 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            },
-            glfw::Key::Num9, glfw::Action::Press => {
-                self.models.iter_mut().enumerate().for_each(|(i, m)| {
-                    if i == 8 {
-                        m.is_selected = true;
-                    } else {
-                        m.is_selected = false;
-                    }
-                });
-            }
+    /// Runs the actions of the keys and buttons of the frame
+    fn run_actions(&mut self) {
+        for action in self.bindings.just_pressed_actions(&self.input) {
+            self.run_action(action);
+        }
+        // keep stepping while the key is held down
+        let step = self.bindings.held_for(&self.input, Action::Step);
+        if step.unwrap_or(0.) > REPEAT_DELAY {
+            self.time.step();
+        }
+
+        process_actions!(
+            self.bindings, &self.input;
+            Action::Quit => self.window.set_should_close(true),
+            Action::ToggleWireframe => unsafe { gl::PolygonMode(gl::FRONT_AND_BACK, gl::LINE) },
         );
+    }
 
-        let window = &self.window;
-        self.models
+    /// Moves the models and cameras `delta_time` seconds forward, with the
+    /// input of the frame unless a name is being typed
+    fn update(&mut self, delta_time: f32) {
+        let idle = Input::default();
+        let input = if self.typed_name.is_some() {
+            &idle
+        } else {
+            &self.input
+        };
+        let bindings = &self.bindings;
+        let model_time = self.time.delta_time(delta_time);
+        let view_orientation = self.cameras[self.active_camera].model_pos.orientation;
+        let translations = self.translations();
+        let scene = Surroundings {
+            translations: &translations,
+            selection_centroid: self.selection_centroid(),
+        };
+        // models animating on their own aren't part of the group
+        let members: Vec<_> = self.selected().filter(|m| !m.is_animating()).collect();
+        let group = match members.first() {
+            Some(first) if members.len() > 1 => Some(Group {
+                centroid: members
+                    .iter()
+                    .fold(vec3(0., 0., 0.), |sum, m| sum + m.translation)
+                    / members.len() as f32,
+                orientation: first.orientation,
+                scale: first.scale,
+            }),
+            _ => None,
+        };
+        self.instances.iter_mut().for_each(|i| {
+            let model = &mut i.position;
+            model.view_orientation = view_orientation;
+            model.group = if model.is_selected && !model.is_animating() {
+                group
+            } else {
+                None
+            };
+            model.process_input(input, bindings, &scene, model_time)
+        });
+
+        self.cameras[self.active_camera].process_input(input, bindings, delta_time);
+        self.input.end_frame();
+        let target = self
+            .instances
+            .iter()
+            .map(|i| &i.position)
+            .find(|m| m.is_selected);
+        if let Some(target) = target {
+            self.cameras
+                .iter_mut()
+                .filter(|c| c.mode == CameraMode::Follow)
+                .for_each(|c| c.follow(target, delta_time));
+        }
+        self.cameras.iter_mut().for_each(|c| {
+            c.update_path(delta_time);
+            c.update_transition(delta_time);
+        });
+    }
+
+    /// Gives input to the camera of the viewport under the cursor, and makes
+    /// the cursor match its mode
+    fn set_active_camera(&mut self, index: usize) {
+        if index == self.active_camera || index >= self.cameras.len() {
+            return;
+        }
+        self.active_camera = index;
+        self.input.mouse_delta = (0., 0.);
+        self.update_cursor_mode();
+    }
+
+    /// Keeps the viewports and aspect ratios matching a new framebuffer size
+    fn resize(&mut self, width: i32, height: i32) {
+        self.wscreen = width.max(0) as u32;
+        self.hscreen = height.max(0) as u32;
+        let (window_width, _) = self.window.get_size();
+        if window_width > 0 {
+            self.scale = width as f32 / window_width as f32;
+        }
+        unsafe { gl::Viewport(0, 0, width, height) }
+    }
+
+    fn toggle_fullscreen(&mut self) {
+        let fullscreen = self.window.with_window_mode(|mode| match mode {
+            glfw::WindowMode::FullScreen(_) => true,
+            glfw::WindowMode::Windowed => false,
+        });
+        if fullscreen {
+            let (x, y, width, height) = self.windowed;
+            self.window
+                .set_monitor(glfw::WindowMode::Windowed, x, y, width, height, None);
+            return;
+        }
+
+        let (x, y) = self.window.get_pos();
+        let (width, height) = self.window.get_size();
+        self.windowed = (x, y, width as u32, height as u32);
+        let (window, monitor) = (&mut self.window, self.monitor);
+        self.glfw.with_connected_monitors(|_, monitors| {
+            let m = match monitors.get(monitor) {
+                Some(m) => m,
+                None => return,
+            };
+            if let Some(mode) = m.get_video_mode() {
+                window.set_monitor(
+                    glfw::WindowMode::FullScreen(m),
+                    0,
+                    0,
+                    mode.width,
+                    mode.height,
+                    Some(mode.refresh_rate),
+                );
+            }
+        });
+    }
+
+    fn update_cursor_mode(&mut self) {
+        // the orbit mode needs a visible cursor to drag with
+        self.window
+            .set_cursor_mode(match self.cameras[self.active_camera].mode {
+                CameraMode::Fly | CameraMode::Follow => glfw::CursorMode::Disabled,
+                CameraMode::Orbit => glfw::CursorMode::Normal,
+            });
+    }
+
+    /// Index of the nearest model under the cursor, or under the center of
+    /// the active viewport while the cursor is captured
+    fn pick(&self) -> Option<usize> {
+        self.pick_point().map(|(i, _)| i)
+    }
+
+    /// Like `pick`, also giving the point that was hit
+    fn pick_point(&self) -> Option<(usize, Vector3<f32>)> {
+        let viewport = *self
+            .layout
+            .viewports(self.wscreen, self.hscreen)
+            .get(self.active_camera)?;
+        let (x, y) = match self.window.get_cursor_mode() {
+            glfw::CursorMode::Normal => {
+                let (x, y) = self.window.get_cursor_pos();
+                let (x, y) = (x as f32 * self.scale, y as f32 * self.scale);
+                (x, self.hscreen as f32 - y)
+            }
+            _ => (
+                viewport.x as f32 + viewport.width as f32 / 2.,
+                viewport.y as f32 + viewport.height as f32 / 2.,
+            ),
+        };
+
+        let camera = &self.cameras[self.active_camera];
+        let projection = camera.projection_matrix(viewport.aspect());
+        let ray = Ray::from_pixel(&projection, &camera.get_view_matrix(), &viewport, x, y)?;
+        let hits: Vec<_> = self
+            .instances
+            .iter()
+            .map(|i| self.models[i.model].pick(&ray, &i.position.matrix()))
+            .collect();
+        let nearest = picking::nearest(hits.iter().cloned())?;
+        Some((nearest, ray.at(hits[nearest]?)))
+    }
+
+    /// Selects the model at `index`, or toggles it when `add` keeps the rest
+    /// of the selection
+    fn select(&mut self, index: usize, add: bool) {
+        if index >= self.instances.len() {
+            return;
+        }
+        if add {
+            let selected = &mut self.instances[index].position.is_selected;
+            *selected = !*selected;
+        } else {
+            self.set_selection(|i| i == index);
+        }
+    }
+
+    /// Selects every model named `name`, returning whether there was any
+    pub fn select_by_name(&mut self, name: &str, add: bool) -> bool {
+        let found = self.instances.iter().any(|i| i.name == name);
+        if found {
+            self.instances
+                .iter_mut()
+                .filter(|i| i.name == name || !add)
+                .for_each(|i| i.position.is_selected = i.name == name);
+        }
+        found
+    }
+
+    /// Edits the name typed after `SelectByName`. Releasing Enter selects
+    /// the models with that name, added to the selection with Ctrl, and
+    /// releasing Escape gives up.
+    fn type_name(&mut self) {
+        let input = &self.input;
+        let name = match &mut self.typed_name {
+            Some(name) => name,
+            None => return,
+        };
+        if input.just_pressed(glfw::Key::Backspace) {
+            name.pop();
+            println!("Select: {}", name);
+        }
+        if input.just_released(glfw::Key::Escape) {
+            self.typed_name = None;
+        } else if input.just_released(glfw::Key::Enter) || input.just_released(glfw::Key::KpEnter) {
+            let add = input.mods().control;
+            let name = self.typed_name.take().unwrap_or_default();
+            if self.select_by_name(&name, add) {
+                println!("Selected: {}", name);
+            } else {
+                println!("No model named {:?}", name);
+            }
+        }
+    }
+
+    /// Moves a single selection `step` models forward, wrapping around
+    fn cycle_selection(&mut self, step: isize) {
+        let n = self.instances.len() as isize;
+        if n == 0 {
+            return;
+        }
+        let current = self
+            .instances
+            .iter()
+            .position(|i| i.position.is_selected)
+            .map_or(-step, |i| i as isize);
+        let next = (current + step).rem_euclid(n) as usize;
+        self.set_selection(|i| i == next);
+        println!("Selected: {}", self.instances[next].name);
+    }
+
+    /// Copies the selected models with their configuration and transform,
+    /// selecting the copies instead
+    fn duplicate_selection(&mut self) {
+        let copies: Vec<_> = self
+            .instances
+            .iter()
+            .filter(|i| i.position.is_selected)
+            .cloned()
+            .collect();
+        self.set_selection(|_| false);
+        for mut copy in copies {
+            copy.name = self.unique_name(&copy.name);
+            println!("Added: {}", copy.name);
+            self.instances.push(copy);
+        }
+    }
+
+    /// Adds an instance of `models[model]` at the focus of the active
+    /// camera, selected alone
+    fn spawn(&mut self, model: usize) {
+        let mut position = self.default_position.clone();
+        position.translation = self.cameras[self.active_camera].focus_point();
+        position.clamp();
+        position.is_selected = true;
+        self.set_selection(|_| false);
+        let name = self.unique_name(&self.model_names[model]);
+        println!("Added: {}", name);
+        self.instances.push(Instance {
+            name,
+            model,
+            shader: self.default_shader,
+            position,
+        });
+    }
+
+    /// Removes the selected models, their meshes stay loaded for new ones
+    fn delete_selection(&mut self) {
+        let count = remove_selected(&mut self.instances, &mut self.default_position);
+        println!("Deleted {} models", count);
+    }
+
+    /// Name not used by other models, see `unique_name`
+    fn unique_name(&self, name: &str) -> String {
+        unique_name(name, |n| self.instances.iter().any(|i| i.name == n))
+    }
+
+    /// First model after `model` that isn't the axis, or `model` when there
+    /// is no other
+    fn next_spawn_model(&self, model: usize) -> usize {
+        let n = self.models.len();
+        (1..=n)
+            .map(|i| (model + i) % n)
+            .find(|&m| m != self.axis_m)
+            .unwrap_or(model)
+    }
+
+    fn set_selection<F>(&mut self, selected: F)
+    where
+        F: Fn(usize) -> bool,
+    {
+        self.instances
             .iter_mut()
-            .for_each(|model| model.process_input(window, delta_time));
+            .enumerate()
+            .for_each(|(i, m)| m.position.is_selected = selected(i));
+    }
 
-        self.camera.process_input(&self.window, delta_time);
+    fn selected(&self) -> impl Iterator<Item = &ModelPosition> {
+        self.instances
+            .iter()
+            .map(|i| &i.position)
+            .filter(|m| m.is_selected)
     }
 
-    fn process_events(&mut self, first_mouse: &mut bool, last_x: &mut f32, last_y: &mut f32) {
+    /// Translation of every model, indexed like the instances
+    fn translations(&self) -> Vec<Vector3<f32>> {
+        self.instances
+            .iter()
+            .map(|i| i.position.translation)
+            .collect()
+    }
+
+    /// Mean translation of the selected models
+    fn selection_centroid(&self) -> Vector3<f32> {
+        let (sum, n) = self.selected().fold((vec3(0., 0., 0.), 0), |(sum, n), m| {
+            (sum + m.translation, n + 1)
+        });
+        if n == 0 {
+            sum
+        } else {
+            sum / n as f32
+        }
+    }
+
+    fn process_events(&mut self) {
         for (_, event) in glfw::flush_messages(&self.events) {
             match event {
-                glfw::WindowEvent::FramebufferSize(width, height) => {
-                    // make sure the viewport matches the new window dimensions
-                    unsafe { gl::Viewport(0, 0, width, height) }
-                }
+                glfw::WindowEvent::FramebufferSize(width, height) => self.resize(width, height),
                 glfw::WindowEvent::CursorPos(xpos, ypos) => {
                     let (xpos, ypos) = (xpos as f32, ypos as f32);
-                    if *first_mouse {
-                        *last_x = xpos;
-                        *last_y = ypos;
-                        *first_mouse = false;
-                    }
+                    self.input.move_cursor(xpos, ypos);
 
-                    let xoffset = xpos - *last_x;
-                    let yoffset = *last_y - ypos;
+                    let pressed = |b| self.window.get_mouse_button(b) == glfw::Action::Press;
+                    let (left, middle) = (
+                        pressed(glfw::MouseButtonLeft),
+                        pressed(glfw::MouseButtonMiddle),
+                    );
 
-                    *last_x = xpos;
-                    *last_y = ypos;
-
-                    self.camera.process_mouse_movement(xoffset, yoffset, true);
+                    // a captured cursor stays on its viewport, a free one
+                    // moves input to whatever viewport it hovers
+                    let mode = self.cameras[self.active_camera].mode;
+                    if mode == CameraMode::Orbit && !left && !middle {
+                        let (x, y) = (xpos * self.scale, ypos * self.scale);
+                        let hovered = self.layout.viewport_at(self.wscreen, self.hscreen, x, y);
+                        if let Some(i) = hovered {
+                            self.set_active_camera(i);
+                        }
+                    }
+                }
+                glfw::WindowEvent::MouseButton(glfw::MouseButtonLeft, glfw::Action::Press, _) => {
+                    self.click = Some(self.window.get_cursor_pos());
+                }
+                glfw::WindowEvent::MouseButton(
+                    glfw::MouseButtonLeft,
+                    glfw::Action::Release,
+                    mods,
+                ) => {
+                    // dragging turns the orbit camera without picking
+                    let (x, y) = self.window.get_cursor_pos();
+                    let picked = match self.click.take() {
+                        Some((cx, cy)) if (x - cx).hypot(y - cy) <= CLICK_DISTANCE => self.pick(),
+                        _ => None,
+                    };
+                    if let Some(i) = picked {
+                        self.select(i, mods.contains(glfw::Modifiers::Control));
+                        println!("Selected: {}", self.instances[i].name);
+                    }
                 }
                 glfw::WindowEvent::Scroll(_xoffset, yoffset) => {
-                    self.camera.process_mouse_scroll(yoffset as f32);
+                    self.input.scroll += yoffset as f32;
+                }
+                glfw::WindowEvent::Key(key, _, glfw::Action::Press, _) => {
+                    self.input.press_key(key);
+                }
+                glfw::WindowEvent::Char(c) => {
+                    if let Some(name) = &mut self.typed_name {
+                        name.push(c);
+                        println!("Select: {}", name);
+                    }
                 }
                 _ => {}
             }
         }
     }
+
+    /// Runs an action started by a key press
+    fn run_action(&mut self, action: Action) {
+        match action {
+            Action::ToggleCameraMode => {
+                self.cameras[self.active_camera].toggle_mode();
+                self.update_cursor_mode();
+                self.input.reset_cursor();
+            }
+            Action::ToggleFollow => {
+                self.cameras[self.active_camera].toggle_follow();
+                self.update_cursor_mode();
+                self.input.reset_cursor();
+            }
+            Action::CycleLayout => {
+                self.layout = self.layout.next();
+                if self.active_camera >= self.layout.count() {
+                    self.active_camera = 0;
+                    self.update_cursor_mode();
+                }
+                let names: Vec<_> = self
+                    .cameras
+                    .iter()
+                    .take(self.layout.count())
+                    .map(|c| c.name.as_str())
+                    .collect();
+                println!("Layout: {:?} {:?}", self.layout, names);
+            }
+            Action::ToggleProjection => self.cameras[self.active_camera].toggle_projection(),
+            Action::Screenshot => self.screenshot = true,
+            Action::ToggleRecording => self.toggle_recording(),
+            Action::ToggleFullscreen => {
+                self.toggle_fullscreen();
+                self.input.reset_cursor();
+            }
+            Action::TogglePause => {
+                self.time.toggle_pause();
+                println!("Paused: {}", self.time.paused);
+            }
+            Action::Step => self.time.step(),
+            Action::Slower => {
+                self.time.slower();
+                println!("Time scale: {}x", self.time.scale());
+            }
+            Action::Faster => {
+                self.time.faster();
+                println!("Time scale: {}x", self.time.scale());
+            }
+            Action::ShowCulling => self.show_culling = !self.show_culling,
+            Action::TogglePath => {
+                let camera = &mut self.cameras[self.active_camera];
+                if camera.path.is_none() {
+                    println!("Camera {:?} has no path", camera.name);
+                }
+                camera.toggle_path();
+            }
+            Action::SelectNext => self.cycle_selection(1),
+            Action::SelectPrevious => self.cycle_selection(-1),
+            Action::SelectByName => {
+                // the key's own character came with the events already read
+                self.typed_name = Some(String::new());
+                println!("Select: type a name, Enter to select or Escape to cancel");
+            }
+            Action::Select(n) => self.select(n - 1, false),
+            Action::ToggleSelect(n) => self.select(n - 1, true),
+            Action::StoreBookmark(n) => {
+                let camera = &self.cameras[self.active_camera];
+                self.bookmarks.0[n - 1] = Some(camera.bookmark());
+                match self.bookmarks.save(&self.bookmarks_path) {
+                    Ok(()) => println!("Stored bookmark {}", n),
+                    Err(e) => eprintln!("Failed to save bookmarks: {}", e),
+                }
+            }
+            Action::RecallBookmark(n) => {
+                if let Some(bookmark) = self.bookmarks.0[n - 1] {
+                    self.cameras[self.active_camera].recall(bookmark);
+                }
+            }
+            Action::FocusOrigin => {
+                self.cameras[self.active_camera].set_focus(vec3(0., 0., 0.));
+            }
+            Action::Duplicate => self.duplicate_selection(),
+            Action::Delete => self.delete_selection(),
+            Action::Spawn => {
+                let model = self.spawn_model;
+                self.spawn(model);
+            }
+            Action::CycleSpawnModel => {
+                self.spawn_model = self.next_spawn_model(self.spawn_model);
+                println!("Spawning: {}", self.model_names[self.spawn_model]);
+            }
+            Action::FocusSelection => {
+                let centroid = self.selection_centroid();
+                self.cameras[self.active_camera].set_focus(centroid);
+            }
+            Action::FocusCursor => {
+                if let Some((_, p)) = self.pick_point() {
+                    self.cameras[self.active_camera].set_focus(p);
+                }
+            }
+            // the rest are held down, see `process_input`
+            _ => {}
+        }
+    }
+}
+
+/// Removes the selected instances and renumbers the models the others and
+/// the placement of new ones use, returning how many were removed
+fn remove_selected(instances: &mut Vec<Instance>, default: &mut ModelPosition) -> usize {
+    let kept: Vec<_> = instances.iter().map(|i| !i.position.is_selected).collect();
+    let translations: Vec<_> = instances.iter().map(|i| i.position.translation).collect();
+    let count = instances.len();
+    instances.retain(|i| !i.position.is_selected);
+    for i in instances.iter_mut() {
+        i.position.remove_models(&kept, &translations);
+    }
+    default.remove_models(&kept, &translations);
+    count - instances.len()
+}
+
+/// `name` without a number suffix, followed by the lowest `.<n>` that isn't
+/// `taken`
+fn unique_name<F>(name: &str, taken: F) -> String
+where
+    F: Fn(&str) -> bool,
+{
+    let base = match name.rfind('.') {
+        Some(i) if i + 1 < name.len() && name[i + 1..].chars().all(|c| c.is_ascii_digit()) => {
+            &name[..i]
+        }
+        _ => name,
+    };
+    if !taken(base) {
+        return base.into();
+    }
+    (1..)
+        .map(|n| format!("{}.{}", base, n))
+        .find(|n| !taken(n))
+        .unwrap()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_pos::{Command, Pivot};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn unique_names() {
+        let names = ["rock", "rock.1", "tree.2"];
+        let taken = |n: &str| names.contains(&n);
+        assert_eq!("arrows", unique_name("arrows", taken));
+        assert_eq!("rock.2", unique_name("rock", taken));
+        assert_eq!("rock.2", unique_name("rock.1", taken));
+        assert_eq!("tree", unique_name("tree.2", taken));
+        assert_eq!("v1.5a", unique_name("v1.5a", taken));
+    }
+
+    #[test]
+    fn removing_renumbers_models() {
+        let instance = |x: f32, selected: bool| {
+            let mut position = ModelPosition::default();
+            position.translation = vec3(x, 0., 0.);
+            position.is_selected = selected;
+            Instance {
+                name: String::new(),
+                model: 0,
+                shader: 0,
+                position,
+            }
+        };
+        let mut instances = vec![instance(0., false), instance(1., true), instance(2., false)];
+        instances[0].position.pivot = Pivot::Model(2);
+        instances[2].position.pivot = Pivot::Model(1);
+        instances[2].position.config.command_list = vec![
+            (Command::LookAtModel(0), 1.),
+            (Command::LookAtModel(1), 1.),
+            (Command::SetPivot(Pivot::Model(2)), 1.),
+        ];
+
+        let mut default = ModelPosition::default();
+        default.config.pivot = Pivot::Model(2);
+        assert_eq!(1, remove_selected(&mut instances, &mut default));
+        assert_eq!(Pivot::Model(1), default.config.pivot);
+        assert_eq!(2, instances.len());
+        assert_eq!(Pivot::Model(1), instances[0].position.pivot);
+        assert_eq!(Pivot::Point([1., 0., 0.]), instances[1].position.pivot);
+        assert_eq!(
+            vec![
+                (Command::LookAtModel(0), 1.),
+                (Command::LookAt([1., 0., 0.]), 1.),
+                (Command::SetPivot(Pivot::Model(1)), 1.),
+            ],
+            instances[1].position.config.command_list
+        );
+    }
 }
 
 // /// utility function for loading a 2D texture from file
@@ -0,0 +1,282 @@
+use crate::{
+    camera::View,
+    model_pos::{ModelPosition, Transform},
+    projection::Projection,
+};
+
+use failure::{ensure, format_err};
+use serde::Deserialize;
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::Read,
+    path::{Path, PathBuf},
+};
+
+const SHADER: &str = "default";
+const AXIS: &str = "axis_arrows";
+/// Distance on X between the models of the default scene
+const MODEL_SPACING: f32 = 2.;
+
+/// Scene as written in JSON, see `configs/scene.json`
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct SceneFile {
+    /// Shader programs by name, instances use `"default"` unless told so
+    pub shaders: BTreeMap<String, ShaderFile>,
+    /// Paths of the `.obj` files by name
+    pub models: BTreeMap<String, String>,
+    /// Model drawn at the origin and on the pivots
+    pub axis: String,
+    pub instances: Vec<InstanceFile>,
+    pub cameras: Vec<CameraFile>,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+pub struct ShaderFile {
+    pub vertex: String,
+    pub fragment: String,
+}
+
+#[derive(Clone, Debug, Default, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct InstanceFile {
+    pub name: Option<String>,
+    pub model: String,
+    pub shader: Option<String>,
+    /// Model configuration, the one given in the command line when missing
+    pub config: Option<PathBuf>,
+    /// Replaces the transform of the configuration
+    pub transform: Option<Transform>,
+    /// Added to the translation, after the transform
+    pub offset: [f32; 3],
+    pub selected: bool,
+}
+
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(default)]
+pub struct CameraFile {
+    pub name: String,
+    pub position: [f32; 3],
+    pub look_at: Option<[f32; 3]>,
+    /// Orbits on an axis aligned view, `position` and `look_at` are ignored
+    pub view: Option<View>,
+    /// The one given in the command line when missing
+    pub projection: Option<Projection>,
+    pub path: Option<PathBuf>,
+}
+
+/// Scene file with the names resolved to indices
+#[derive(Clone, Debug, PartialEq)]
+pub struct Resolved {
+    pub shaders: Vec<ShaderFile>,
+    pub models: Vec<String>,
+    /// Index of the axis in `models`
+    pub axis: usize,
+    /// Index of the shader used by instances that don't name one
+    pub shader: usize,
+    /// Name, model and shader of each instance
+    pub instances: Vec<(String, usize, usize)>,
+}
+
+impl Default for SceneFile {
+    fn default() -> Self {
+        SceneFile::with_models(1)
+    }
+}
+
+impl Default for CameraFile {
+    fn default() -> Self {
+        CameraFile {
+            name: String::from("camera"),
+            position: [0., 1., 20.],
+            look_at: None,
+            view: None,
+            projection: None,
+            path: None,
+        }
+    }
+}
+
+impl SceneFile {
+    /// Scene with `n_models` axis arrows in a row, the first one selected,
+    /// and a camera for each viewport of the quad layout
+    pub fn with_models(n_models: usize) -> Self {
+        let mut shaders = BTreeMap::new();
+        shaders.insert(
+            SHADER.into(),
+            ShaderFile {
+                vertex: "resources/cg_ufpel.vs".into(),
+                fragment: "resources/cg_ufpel.fs".into(),
+            },
+        );
+        let mut models = BTreeMap::new();
+        models.insert(
+            AXIS.into(),
+            "resources/objects/axis_arrows/axis_arrows.obj".into(),
+        );
+
+        let instances = (0..n_models)
+            .map(|i| InstanceFile {
+                model: AXIS.into(),
+                offset: [i as f32 * MODEL_SPACING, 0., 0.],
+                selected: i == 0,
+                ..InstanceFile::default()
+            })
+            .collect();
+
+        let camera = |name: &str, view| CameraFile {
+            name: name.into(),
+            view,
+            ..CameraFile::default()
+        };
+        SceneFile {
+            shaders,
+            models,
+            axis: AXIS.into(),
+            instances,
+            cameras: vec![
+                camera("main", None),
+                camera("front", Some(View::Front)),
+                camera("side", Some(View::Side)),
+                camera("top", Some(View::Top)),
+            ],
+        }
+    }
+
+    pub fn from_path<P>(path: P) -> Result<SceneFile, failure::Error>
+    where
+        P: AsRef<Path>,
+    {
+        let mut content = String::default();
+        fs::File::open(path)?.read_to_string(&mut content)?;
+        serde_json::from_str(&content)
+            .map_err(|e| format_err!("Failed to read scene file: {:#?}", e))
+    }
+
+    /// Checks the names used by the instances and the axis, and replaces
+    /// them by indices. Unnamed instances are named after their model.
+    pub fn resolve(&self) -> Result<Resolved, failure::Error> {
+        ensure!(!self.cameras.is_empty(), "Scene needs at least one camera");
+        ensure!(!self.shaders.is_empty(), "Scene needs at least one shader");
+        let index = |names: Vec<&String>, name: &str, kind: &str| {
+            names
+                .iter()
+                .position(|n| n.as_str() == name)
+                .ok_or_else(|| format_err!("Unknown {} {:?} in scene file", kind, name))
+        };
+        let model = |name: &str| index(self.models.keys().collect(), name, "model");
+        let shader = |name: &str| index(self.shaders.keys().collect(), name, "shader");
+
+        let instances = self
+            .instances
+            .iter()
+            .map(|i| {
+                let name = i.name.clone().unwrap_or_else(|| i.model.clone());
+                let s = i.shader.as_ref().map_or(SHADER, String::as_str);
+                Ok((name, model(&i.model)?, shader(s)?))
+            })
+            .collect::<Result<_, failure::Error>>()?;
+
+        Ok(Resolved {
+            shaders: self.shaders.values().cloned().collect(),
+            models: self.models.values().cloned().collect(),
+            axis: model(&self.axis)?,
+            shader: shader(SHADER).unwrap_or(0),
+            instances,
+        })
+    }
+}
+
+impl Resolved {
+    /// Checks the model and shader files exist, as loading them panics
+    pub fn check_files(&self) -> Result<(), failure::Error> {
+        let shaders = self
+            .shaders
+            .iter()
+            .flat_map(|s| vec![&s.vertex, &s.fragment]);
+        for path in self.models.iter().chain(shaders) {
+            ensure!(
+                Path::new(path).is_file(),
+                "Can't find {:?} from the scene",
+                path
+            );
+        }
+        Ok(())
+    }
+}
+
+impl InstanceFile {
+    /// Starting position of the instance, from its own configuration or
+    /// `default`
+    pub fn position(&self, default: &ModelPosition) -> Result<ModelPosition, failure::Error> {
+        let mut position = match &self.config {
+            Some(path) => ModelPosition::with_config(path)?,
+            None => default.clone(),
+        };
+        if let Some(t) = &self.transform {
+            position.set_transform(t);
+        }
+        position.translation += self.offset.into();
+        position.clamp();
+        position.is_selected = self.selected;
+        Ok(position)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn resolve_names() {
+        let scene: SceneFile = serde_json::from_value(json!({
+            "models": {
+                "axis_arrows": "resources/objects/axis_arrows/axis_arrows.obj",
+                "rock": "resources/objects/rock/rock.obj",
+            },
+            "instances": [
+                { "model": "rock", "selected": true },
+                { "name": "arrows", "model": "axis_arrows" },
+            ],
+        }))
+        .unwrap();
+        let resolved = scene.resolve().unwrap();
+        assert_eq!(
+            vec![(String::from("rock"), 1, 0), (String::from("arrows"), 0, 0)],
+            resolved.instances
+        );
+        assert_eq!(0, resolved.axis);
+        assert_eq!(0, resolved.shader);
+        assert_eq!(4, scene.cameras.len());
+
+        let mut unknown = scene.clone();
+        unknown.instances[0].shader = Some("toon".into());
+        assert!(unknown.resolve().is_err());
+
+        let mut missing = resolved.clone();
+        missing.models[1] = "resources/objects/rock/rokc.obj".into();
+        assert!(missing.check_files().is_err());
+    }
+
+    #[test]
+    fn default_scene_keeps_config_transform() {
+        let mut default = ModelPosition::default();
+        default.set_transform(&Transform {
+            translation: [0., 1., 0.],
+            rotation: [0., 90., 0.],
+            scale: [2., 2., 2.],
+        });
+        let scene = SceneFile::with_models(3);
+        let position = scene.instances[2].position(&default).unwrap();
+        assert_eq!(default.orientation, position.orientation);
+        assert_eq!(default.scale, position.scale);
+        assert_eq!(
+            [2. * MODEL_SPACING, 1., 0.],
+            position.transform().translation
+        );
+        assert!(!position.is_selected);
+    }
+}
@@ -0,0 +1,80 @@
+/// Speeds the simulation can run at, `Minus` and `Equal` move between them
+const SCALES: [f32; 4] = [0.25, 0.5, 1., 2.];
+const NORMAL_SCALE: usize = 2;
+
+/// Seconds advanced by a single step while paused
+pub const STEP: f32 = 1. / 60.;
+
+/// Scales the frame time given to models, leaving the cameras untouched
+#[derive(Clone, Debug, PartialEq)]
+pub struct TimeControl {
+    scale: usize,
+    pub paused: bool,
+    /// Advance one `STEP` on the next frame, even while paused
+    step: bool,
+}
+
+impl Default for TimeControl {
+    fn default() -> Self {
+        TimeControl {
+            scale: NORMAL_SCALE,
+            paused: false,
+            step: false,
+        }
+    }
+}
+
+impl TimeControl {
+    pub fn scale(&self) -> f32 {
+        SCALES[self.scale]
+    }
+
+    pub fn faster(&mut self) {
+        self.scale = (self.scale + 1).min(SCALES.len() - 1);
+    }
+
+    pub fn slower(&mut self) {
+        self.scale = self.scale.saturating_sub(1);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+    }
+
+    /// Requests a single step, only taken while paused
+    pub fn step(&mut self) {
+        self.step = self.paused;
+    }
+
+    /// Simulation time for a frame that took `delta_time` seconds
+    pub fn delta_time(&mut self, delta_time: f32) -> f32 {
+        if self.step {
+            self.step = false;
+            STEP
+        } else if self.paused {
+            0.
+        } else {
+            delta_time * self.scale()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn pause_and_step() {
+        let mut time = TimeControl::default();
+        time.slower();
+        assert_eq!(0.5, time.delta_time(1.));
+
+        time.step();
+        time.toggle_pause();
+        assert_eq!(0., time.delta_time(1.));
+        time.step();
+        assert_eq!(STEP, time.delta_time(1.));
+        assert_eq!(0., time.delta_time(1.));
+    }
+}
@@ -0,0 +1,114 @@
+use serde::Deserialize;
+
+/// How the window is divided between cameras
+#[derive(Copy, Clone, Debug, Deserialize, PartialEq)]
+pub enum Layout {
+    Single,
+    /// Two side by side viewports
+    Split,
+    /// Four viewports, in reading order
+    Quad,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::Single
+    }
+}
+
+/// Rectangle of the framebuffer, with the origin at the bottom left corner
+/// like `gl::Viewport` expects
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Viewport {
+    pub x: i32,
+    pub y: i32,
+    pub width: u32,
+    pub height: u32,
+}
+
+impl Layout {
+    pub fn next(self) -> Self {
+        match self {
+            Layout::Single => Layout::Split,
+            Layout::Split => Layout::Quad,
+            Layout::Quad => Layout::Single,
+        }
+    }
+
+    pub fn count(self) -> usize {
+        match self {
+            Layout::Single => 1,
+            Layout::Split => 2,
+            Layout::Quad => 4,
+        }
+    }
+
+    pub fn viewports(self, width: u32, height: u32) -> Vec<Viewport> {
+        let (hw, hh) = (width / 2, height / 2);
+        match self {
+            Layout::Single => vec![Viewport::new(0, 0, width, height)],
+            Layout::Split => vec![
+                Viewport::new(0, 0, hw, height),
+                Viewport::new(hw as i32, 0, width - hw, height),
+            ],
+            Layout::Quad => vec![
+                Viewport::new(0, hh as i32, hw, height - hh),
+                Viewport::new(hw as i32, hh as i32, width - hw, height - hh),
+                Viewport::new(0, 0, hw, hh),
+                Viewport::new(hw as i32, 0, width - hw, hh),
+            ],
+        }
+    }
+
+    /// Index of the viewport under a cursor given in window coordinates, with
+    /// the origin at the top left corner
+    pub fn viewport_at(self, width: u32, height: u32, x: f32, y: f32) -> Option<usize> {
+        let y = height as f32 - y;
+        self.viewports(width, height)
+            .iter()
+            .position(|v| v.contains(x, y))
+    }
+}
+
+impl Viewport {
+    pub fn new(x: i32, y: i32, width: u32, height: u32) -> Self {
+        Viewport {
+            x,
+            y,
+            width,
+            height,
+        }
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.width as f32 / self.height.max(1) as f32
+    }
+
+    fn contains(&self, x: f32, y: f32) -> bool {
+        x >= self.x as f32
+            && x < (self.x + self.width as i32) as f32
+            && y >= self.y as f32
+            && y < (self.y + self.height as i32) as f32
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn quad_viewports() {
+        let v = Layout::Quad.viewports(800, 600);
+        assert_eq!(Viewport::new(0, 300, 400, 300), v[0]);
+        assert_eq!(Viewport::new(400, 0, 400, 300), v[3]);
+    }
+
+    #[test]
+    fn cursor_lookup() {
+        assert_eq!(Some(0), Layout::Quad.viewport_at(800, 600, 10., 10.));
+        assert_eq!(Some(3), Layout::Quad.viewport_at(800, 600, 790., 590.));
+        assert_eq!(Some(1), Layout::Split.viewport_at(800, 600, 500., 10.));
+        assert_eq!(None, Layout::Single.viewport_at(800, 600, 900., 10.));
+    }
+}